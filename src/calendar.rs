@@ -0,0 +1,193 @@
+use crate::duration::deserialize_opt_duration;
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+
+/// The reason why no work is expected on a day.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum DayOff {
+    Holiday,
+    Vacation,
+    Sick,
+}
+
+impl Display for DayOff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            DayOff::Holiday => "holiday",
+            DayOff::Vacation => "vacation",
+            DayOff::Sick => "sick day",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Days that differ from the usual weekly targets. Stored inside the [TimeLog](crate::time_log::TimeLog).
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Calendar {
+    days_off: BTreeMap<NaiveDate, DayOff>,
+}
+
+impl Calendar {
+    pub fn is_empty(&self) -> bool {
+        self.days_off.is_empty()
+    }
+
+    pub fn mark(&mut self, date: NaiveDate, kind: DayOff) -> Option<DayOff> {
+        self.days_off.insert(date, kind)
+    }
+
+    pub fn unmark(&mut self, date: NaiveDate) -> Option<DayOff> {
+        self.days_off.remove(&date)
+    }
+
+    pub fn day_off(&self, date: NaiveDate) -> Option<DayOff> {
+        self.days_off.get(&date).copied()
+    }
+
+    pub fn days_off(&self) -> impl Iterator<Item = (&NaiveDate, &DayOff)> {
+        self.days_off.iter()
+    }
+}
+
+/// The per-weekday targets as configured in the `[targets]` table of the settings.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WeeklyTargets {
+    #[serde(deserialize_with = "deserialize_opt_duration")]
+    pub mon: Option<Duration>,
+    #[serde(deserialize_with = "deserialize_opt_duration")]
+    pub tue: Option<Duration>,
+    #[serde(deserialize_with = "deserialize_opt_duration")]
+    pub wed: Option<Duration>,
+    #[serde(deserialize_with = "deserialize_opt_duration")]
+    pub thu: Option<Duration>,
+    #[serde(deserialize_with = "deserialize_opt_duration")]
+    pub fri: Option<Duration>,
+    #[serde(deserialize_with = "deserialize_opt_duration")]
+    pub sat: Option<Duration>,
+    #[serde(deserialize_with = "deserialize_opt_duration")]
+    pub sun: Option<Duration>,
+}
+
+impl WeeklyTargets {
+    fn get(&self, day: Weekday) -> Option<Duration> {
+        match day {
+            Weekday::Mon => self.mon,
+            Weekday::Tue => self.tue,
+            Weekday::Wed => self.wed,
+            Weekday::Thu => self.thu,
+            Weekday::Fri => self.fri,
+            Weekday::Sat => self.sat,
+            Weekday::Sun => self.sun,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        [
+            self.mon, self.tue, self.wed, self.thu, self.fri, self.sat, self.sun,
+        ]
+        .iter()
+        .all(Option::is_none)
+    }
+}
+
+/// The amount of time that should be tracked per day.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Targets {
+    per_weekday: [Duration; 7],
+}
+
+impl Targets {
+    /// Combines the weekly targets with the `daily_target`, which applies to all working days
+    /// (Monday to Friday) that have no explicit target.
+    pub fn new(weekly: &WeeklyTargets, daily_target: Option<Duration>) -> Option<Targets> {
+        if weekly.is_empty() && daily_target.is_none() {
+            return None;
+        }
+        let mut per_weekday = [Duration::zero(); 7];
+        let mut day = Weekday::Mon;
+        for target in per_weekday.iter_mut() {
+            let working_day = !matches!(day, Weekday::Sat | Weekday::Sun);
+            *target = weekly
+                .get(day)
+                .or(daily_target.filter(|_| working_day))
+                .unwrap_or_else(Duration::zero);
+            day = day.succ();
+        }
+        Some(Targets { per_weekday })
+    }
+
+    pub fn for_date(&self, date: NaiveDate, calendar: &Calendar) -> Duration {
+        match calendar.day_off(date) {
+            Some(_) => Duration::zero(),
+            None => self.per_weekday[date.weekday().num_days_from_monday() as usize],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn monday() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 9, 7).unwrap()
+    }
+
+    #[test]
+    fn no_targets_configured() {
+        assert!(Targets::new(&WeeklyTargets::default(), None).is_none());
+    }
+
+    #[test]
+    fn daily_target_applies_to_working_days() {
+        let t = Targets::new(&WeeklyTargets::default(), Some(Duration::hours(8))).unwrap();
+        let cal = Calendar::default();
+
+        assert_eq!(t.for_date(monday(), &cal), Duration::hours(8));
+        assert_eq!(
+            t.for_date(monday() + Duration::days(4), &cal),
+            Duration::hours(8)
+        );
+        assert_eq!(
+            t.for_date(monday() + Duration::days(5), &cal),
+            Duration::zero()
+        );
+    }
+
+    #[test]
+    fn weekly_targets_override_daily_target() {
+        let weekly = WeeklyTargets {
+            fri: Some(Duration::hours(6)),
+            sat: Some(Duration::hours(2)),
+            ..Default::default()
+        };
+        let t = Targets::new(&weekly, Some(Duration::hours(8))).unwrap();
+        let cal = Calendar::default();
+
+        assert_eq!(t.for_date(monday(), &cal), Duration::hours(8));
+        assert_eq!(
+            t.for_date(monday() + Duration::days(4), &cal),
+            Duration::hours(6)
+        );
+        assert_eq!(
+            t.for_date(monday() + Duration::days(5), &cal),
+            Duration::hours(2)
+        );
+    }
+
+    #[test]
+    fn days_off_have_no_target() {
+        let t = Targets::new(&WeeklyTargets::default(), Some(Duration::hours(8))).unwrap();
+        let mut cal = Calendar::default();
+        cal.mark(monday(), DayOff::Holiday);
+
+        assert_eq!(t.for_date(monday(), &cal), Duration::zero());
+        cal.unmark(monday());
+        assert_eq!(t.for_date(monday(), &cal), Duration::hours(8));
+    }
+}
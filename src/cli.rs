@@ -1,9 +1,27 @@
-use clap::{crate_authors, crate_version, Parser};
+use clap::{crate_authors, crate_version, Parser, ValueEnum};
+use clap_complete::engine::{ArgValueCandidates, CompletionCandidate};
 use clap_complete::Shell;
 
+use crate::calendar::DayOff;
+use crate::csv::parse_delimiter;
+use crate::duration::parse_duration;
+use crate::git_hooks::GitHook;
+use crate::idle::{parse_time, IdlePolicy};
+use crate::import::ImportSource;
+use crate::invoice::Period;
+use crate::persistence::{load_or_create_log, FsFileHandler};
+use crate::pretty_string::DurationFormat;
+use crate::project::{BudgetPeriod, ProjectPattern};
+use crate::rounding::Rounding;
+use chrono::{Duration, NaiveDate, NaiveTime};
+use std::path::PathBuf;
+
 pub const DEFAULT_STATUS_FORMAT: &str = "Tracking %p since %d (%t) [%D]";
 pub const DEFAULT_EMPTY_STATUS_MSG: &str = "Currently tracking no project.";
 pub const ENV_TRACKIE_CONFIG: &str = "TRACKIE_CONFIG";
+pub const ENV_TRACKIE_SETTINGS: &str = "TRACKIE_SETTINGS";
+/// The exit code of `status --json` if no time is currently tracked.
+pub const EXIT_NOT_TRACKING: i32 = 3;
 
 #[derive(Parser)]
 #[clap(author=crate_authors!(), version=crate_version!())]
@@ -16,24 +34,60 @@ pub struct Opts {
 #[derive(Parser)]
 pub enum Subcommand {
     /// Starts the time tracking for a project
+    ///
+    /// Without a project, it is taken from the nearest `.trackie` file or git repository. Outside of
+    /// those, a picker lists the recently and frequently tracked projects.
     Start(TimingCommand),
+    /// Switches the time tracking to a project picked from the recently and frequently tracked ones
+    Switch(TimingCommand),
     /// Stops the time tracking for a project
-    Stop(EmptyCommand),
+    Stop(StopCommand),
     /// Creates a report for the logged times
     Report(ReportCommand),
     /// Shows information about the currently tracked work log, if present
     ///
     /// The commands supports the following variables in its format strings (provided via -f):
     ///     - %p: The name of the project
-    ///     - %d: The date on which the tracking started
-    ///     - %t: The time at which the tracking started
+    ///     - %d: The date on which the tracking started (%d{<strftime>} for a custom format)
+    ///     - %t: The time at which the tracking started (%t{<strftime>} for a custom format)
     ///     - %D: The duration of the current tracking
+    ///     - %s: The seconds elapsed since the tracking started
+    ///     - %T: The total time tracked today, including the current tracking
+    ///     - %W: The total time tracked this week, including the current tracking
+    ///     - %n: The note of the current tracking
+    ///     - %g: The tags of the current tracking, separated by commas
+    ///     - %R: The time remaining to reach the daily target
+    ///     - %B: The budget remaining for the project, empty if it has no budget
+    ///     - %%: A literal %
+    ///
+    /// Durations accept a format selector, e.g. %D{decimal}. Placeholders can be padded
+    /// to a width, e.g. %-20p (left-aligned) or %06s (zero-padded).
     ///
     #[clap(verbatim_doc_comment)]
     Status(StatusCommand),
     /// Resumes time tracking for the last tracked project.
     #[clap(visible_alias = "rs")]
-    Resume(EmptyCommand),
+    Resume(ResumeCommand),
+    /// Shows whether you are ahead or behind your configured targets
+    Balance(BalanceCommand),
+    /// Opens a full-screen dashboard with the running timer, today's entries and the week
+    Tui(EmptyCommand),
+    /// Creates an invoice for the time tracked on a project
+    Invoice(InvoiceCommand),
+    /// Manages the registered projects and their metadata, like budgets
+    Project(ProjectCommand),
+    /// Marks holidays, vacation and sick days, on which no work is expected
+    Calendar(CalendarCommand),
+    /// Exports all tracked entries, e.g. for further processing in a spreadsheet or calendar
+    Export(ExportCommand),
+    /// Imports entries exported from Toggl Track, Watson or Timewarrior
+    Import(ImportCommand),
+    /// Manages git hooks that track the time on a repository automatically
+    ///
+    /// After a branch is checked out or a commit is created, the tracking switches to the project
+    /// of the repository, like `trackie start` without a project. Commit subjects are added to the
+    /// note of the tracked work.
+    Hooks(HooksCommand),
     /// Generate tab-completion scripts for your shell
     Completion(CompletionCommand),
 }
@@ -44,6 +98,267 @@ pub struct CompletionCommand {
     pub shell: Shell,
 }
 
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ReportFormat {
+    Text,
+    Json,
+    Csv,
+    /// An Org table
+    Org,
+    /// A GitHub flavored Markdown table per day
+    Markdown,
+    /// A self-contained HTML page with charts
+    Html,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    /// One row per tracked entry
+    Csv,
+    /// One iCalendar event per tracked entry
+    Ics,
+    /// Clock-in and clock-out lines for ledger and hledger
+    Timeclock,
+}
+
+#[derive(Parser)]
+pub struct CsvOptions {
+    /// The field delimiter of CSV output
+    #[clap(long, default_value = ",", value_parser = parse_delimiter)]
+    pub delimiter: char,
+
+    /// Omits the header row of CSV output
+    #[clap(long)]
+    pub no_header: bool,
+}
+
+#[derive(Parser)]
+pub struct ExportCommand {
+    /// The output format
+    #[clap(long, value_enum, default_value = "csv")]
+    pub format: ExportFormat,
+
+    /// The first day to export
+    #[clap(long)]
+    pub from: Option<NaiveDate>,
+
+    /// The last day to export
+    #[clap(long)]
+    pub to: Option<NaiveDate>,
+
+    #[clap(flatten)]
+    pub csv: CsvOptions,
+}
+
+#[derive(Parser)]
+pub struct ImportCommand {
+    /// The tool that created the file
+    #[clap(long = "from", value_enum)]
+    pub source: ImportSource,
+
+    /// The file to import
+    pub file: PathBuf,
+
+    /// Only print the entries that would be imported
+    #[clap(long)]
+    pub dry_run: bool,
+}
+
+#[derive(Parser)]
+pub struct HooksCommand {
+    #[clap(subcommand)]
+    pub action: HooksAction,
+}
+
+#[derive(Parser)]
+pub enum HooksAction {
+    /// Installs the hooks in the git repository of the current directory
+    Install,
+    /// Removes the hooks from the git repository of the current directory
+    Uninstall,
+    /// Called by the installed hooks
+    #[clap(hide = true)]
+    Run {
+        #[clap(value_enum)]
+        hook: GitHook,
+        /// The arguments git passed to the hook
+        #[clap(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum InvoiceFormat {
+    Json,
+    Markdown,
+    /// A self-contained HTML page
+    Html,
+}
+
+#[derive(Parser)]
+pub struct InvoiceCommand {
+    /// The project to bill
+    #[clap(long, add = ArgValueCandidates::new(complete_projects))]
+    pub project: String,
+
+    /// The month to bill, e.g. `2026-09`
+    #[clap(long)]
+    pub period: Period,
+
+    /// The output format of the invoice
+    #[clap(long, value_enum, default_value = "markdown")]
+    pub format: InvoiceFormat,
+
+    /// The billing increment, e.g. `15m:up`. Defaults to the `round` setting of `[invoice]`.
+    #[clap(long)]
+    pub round: Option<Rounding>,
+
+    /// Writes the invoice to the given file instead of stdout.
+    #[clap(short, long)]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Parser)]
+pub struct BalanceCommand {
+    /// The first day to include. Defaults to the `balance_start` setting or the first tracked day.
+    #[clap(long)]
+    pub from: Option<NaiveDate>,
+
+    /// The last day to include. Defaults to today.
+    #[clap(long)]
+    pub to: Option<NaiveDate>,
+
+    /// Dump balance as JSON
+    #[clap(long)]
+    pub json: bool,
+
+    /// The format in which durations are printed.
+    #[clap(long, value_enum)]
+    pub duration_format: Option<DurationFormat>,
+}
+
+#[derive(Parser)]
+pub struct ProjectCommand {
+    #[clap(subcommand)]
+    pub action: ProjectAction,
+}
+
+#[derive(Parser)]
+pub enum ProjectAction {
+    /// Registers a project, or updates the properties of a registered one
+    Add {
+        /// The name of the project
+        #[clap(add = ArgValueCandidates::new(complete_projects))]
+        name: String,
+        /// What the project is about
+        #[clap(long)]
+        description: Option<String>,
+        /// The colour of the project in charts, e.g. `#4e79a7` or `teal`
+        #[clap(long)]
+        color: Option<String>,
+        /// The client the project is done for
+        #[clap(long)]
+        client: Option<String>,
+        /// The hourly rate, used for invoices if the settings configure none
+        #[clap(long)]
+        rate: Option<f64>,
+        /// The local git repository of the project, whose commits `report --with-commits` lists
+        #[clap(long)]
+        repo: Option<PathBuf>,
+    },
+    /// Lists all tracked and registered projects
+    List {
+        /// Includes archived projects
+        #[clap(long)]
+        all: bool,
+    },
+    /// Renames a project and its subprojects, including all of their tracked entries
+    Rename {
+        /// The current name of the project
+        #[clap(add = ArgValueCandidates::new(complete_projects))]
+        name: String,
+        /// The new name of the project
+        new_name: String,
+        /// Only print the number of entries that would be renamed
+        #[clap(long)]
+        dry_run: bool,
+    },
+    /// Merges projects and their subprojects into another project, including all of their tracked entries
+    Merge {
+        /// The projects to merge, followed by the project to merge them into
+        #[clap(num_args = 2.., required = true, add = ArgValueCandidates::new(complete_projects))]
+        projects: Vec<String>,
+        /// Only print the number of entries that would be merged
+        #[clap(long)]
+        dry_run: bool,
+    },
+    /// Archives a project, hiding it from `project list`
+    Archive {
+        /// The name of the project
+        #[clap(add = ArgValueCandidates::new(complete_projects))]
+        name: String,
+        /// Restores an archived project instead
+        #[clap(long)]
+        undo: bool,
+    },
+    /// Adds another name for a project, e.g. to start tracking with a shorter name
+    Alias {
+        /// The name of the project
+        #[clap(add = ArgValueCandidates::new(complete_projects))]
+        name: String,
+        /// The other name
+        alias: String,
+        /// Removes the alias instead
+        #[clap(long)]
+        remove: bool,
+    },
+    /// Shows the metadata and tracked time of a project
+    Info {
+        /// The name of the project
+        #[clap(add = ArgValueCandidates::new(complete_projects))]
+        name: String,
+    },
+    /// Sets the time budget of a project, or shows how much of it is left
+    Budget {
+        /// The name of the project
+        #[clap(add = ArgValueCandidates::new(complete_projects))]
+        name: String,
+        /// The budget, e.g. `40h`. Shows the current budget status if omitted.
+        #[clap(value_parser = parse_duration, conflicts_with = "remove")]
+        budget: Option<Duration>,
+        /// The period after which the budget starts anew
+        #[clap(long, value_enum, default_value = "total")]
+        per: BudgetPeriod,
+        /// Removes the budget of the project
+        #[clap(long)]
+        remove: bool,
+    },
+}
+
+#[derive(Parser)]
+pub struct CalendarCommand {
+    #[clap(subcommand)]
+    pub action: CalendarAction,
+}
+
+#[derive(Parser)]
+pub enum CalendarAction {
+    /// Marks a day as day off
+    Add {
+        /// The day, e.g. 2026-12-24
+        date: NaiveDate,
+        #[clap(value_enum)]
+        kind: DayOff,
+    },
+    /// Removes a day off
+    Remove {
+        /// The day, e.g. 2026-12-24
+        date: NaiveDate,
+    },
+    /// Lists all days off
+    List,
+}
+
 #[derive(Parser)]
 pub struct StatusCommand {
     /// A format string describing the output of the command.
@@ -53,6 +368,50 @@ pub struct StatusCommand {
     /// The message that gets printed to the console if no time is currently tracked.
     #[clap(long)]
     pub fallback: Option<String>,
+
+    /// The default format in which durations are printed.
+    #[clap(long, value_enum)]
+    pub duration_format: Option<DurationFormat>,
+
+    /// Warns if the current directory belongs to another project than the tracked one.
+    #[clap(long)]
+    pub check_project: bool,
+
+    #[clap(flatten)]
+    pub idle: IdleOptions,
+
+    /// Prints the status as JSON. Exits with code 3 if no time is currently tracked.
+    #[clap(long, conflicts_with_all = ["format", "fallback", "duration_format"])]
+    pub json: bool,
+}
+
+#[derive(Parser)]
+pub struct StopCommand {
+    /// The format in which the tracked duration is printed.
+    #[clap(long, value_enum)]
+    pub duration_format: Option<DurationFormat>,
+
+    #[clap(flatten)]
+    pub idle: IdleOptions,
+}
+
+#[derive(Parser)]
+pub struct ResumeCommand {
+    #[clap(flatten)]
+    pub idle: IdleOptions,
+}
+
+/// Handles work that has been tracked for longer than the `idle_threshold` setting, e.g. because
+/// the tracking was not stopped before lunch. Without these options, the user is asked.
+#[derive(Parser, Default)]
+pub struct IdleOptions {
+    /// How to handle work that has been tracked for suspiciously long
+    #[clap(long, value_enum)]
+    pub idle_policy: Option<IdlePolicy>,
+
+    /// The time (HH:MM) at which the idle work ended. Defaults to when the threshold was exceeded.
+    #[clap(long, value_parser = parse_time)]
+    pub idle_end: Option<NaiveTime>,
 }
 
 #[derive(Parser)]
@@ -60,8 +419,17 @@ pub struct EmptyCommand {}
 
 #[derive(Parser)]
 pub struct TimingCommand {
-    /// The name of the project
-    pub project_name: String,
+    /// The name of the project. Detected from the working directory or picked interactively if omitted.
+    #[clap(add = ArgValueCandidates::new(complete_projects))]
+    pub project_name: Option<String>,
+
+    /// A note describing the tracked work
+    #[clap(short, long)]
+    pub note: Option<String>,
+
+    /// A tag for the tracked work. Can be given multiple times.
+    #[clap(short, long = "tag")]
+    pub tags: Vec<String>,
 }
 
 #[derive(Parser)]
@@ -74,7 +442,65 @@ pub struct ReportCommand {
     #[clap(short, long)]
     pub include_empty_days: bool,
 
-    /// Dump report as JSON
-    #[clap(long)]
+    /// Dump report as JSON. Same as `--format json`.
+    #[clap(long, conflicts_with = "format")]
     pub json: bool,
+
+    /// The output format of the report
+    #[clap(long, value_enum, default_value = "text")]
+    pub format: ReportFormat,
+
+    #[clap(flatten)]
+    pub csv: CsvOptions,
+
+    /// Rounds the tracked times, e.g. `15m:up`. Format: <granularity>:<up|down|nearest>[:<entry|day|report>]
+    #[clap(long)]
+    pub round: Option<Rounding>,
+
+    /// The format in which durations are printed.
+    #[clap(long, value_enum)]
+    pub duration_format: Option<DurationFormat>,
+
+    /// Writes the report to the given file instead of stdout.
+    #[clap(short, long)]
+    pub output: Option<PathBuf>,
+    /// Renders the report with a MiniJinja template file instead of a built-in format.
+    #[clap(long, conflicts_with_all = ["format", "json"])]
+    pub template: Option<PathBuf>,
+
+    /// Collapses hierarchical projects like `client/project/task` to the given number of levels.
+    #[clap(long, value_parser = clap::value_parser!(u32).range(1..))]
+    pub depth: Option<u32>,
+
+    /// Only reports the given project. `client/*` includes all of its subprojects.
+    #[clap(long, add = ArgValueCandidates::new(complete_projects))]
+    pub project: Option<ProjectPattern>,
+
+    /// Shows the total per project over all reported days, together with its budget.
+    #[clap(long, conflicts_with = "template")]
+    pub summary: bool,
+
+    /// Lists the commits authored during the tracked entries, read from the repositories
+    /// linked to the projects or configured in the settings.
+    #[clap(long, conflicts_with = "summary")]
+    pub with_commits: bool,
+}
+
+/// Completes the names of all projects and aliases, the most recently tracked projects first.
+fn complete_projects() -> Vec<CompletionCandidate> {
+    let log = load_or_create_log(&FsFileHandler::new()).unwrap_or_default();
+    let projects = log.projects_by_recency().into_iter().map(|name| {
+        let description = log.projects.get(name).and_then(|p| p.description.clone());
+        CompletionCandidate::new(name).help(description.map(Into::into))
+    });
+    let aliases = log.projects.iter().flat_map(|(name, p)| {
+        p.aliases.iter().map(move |alias| {
+            CompletionCandidate::new(alias).help(Some(format!("Alias of {}", name).into()))
+        })
+    });
+    projects
+        .chain(aliases)
+        .enumerate()
+        .map(|(idx, c)| c.display_order(Some(idx)))
+        .collect()
 }
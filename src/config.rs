@@ -0,0 +1,149 @@
+use crate::calendar::{Targets, WeeklyTargets};
+use crate::duration::deserialize_opt_duration;
+use crate::events::EventHooks;
+use crate::invoice::{HourlyRate, InvoiceSettings};
+use crate::pretty_string::DurationFormat;
+use crate::project::UnknownProjects;
+use crate::rounding::Rounding;
+use chrono::{Duration, NaiveDate};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// User settings, read from the `config.toml` file.
+///
+/// Unlike the [TimeLog](crate::time_log::TimeLog), this file is never written by trackie.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// The rounding policy applied to reports, e.g. `15m:up`.
+    pub round: Option<Rounding>,
+    /// The default format in which durations are printed.
+    pub duration_format: Option<DurationFormat>,
+    /// The amount of time that should be tracked on each working day, e.g. `8h`.
+    #[serde(deserialize_with = "deserialize_opt_duration")]
+    pub daily_target: Option<Duration>,
+    /// Targets for single weekdays, overriding the `daily_target`.
+    pub targets: WeeklyTargets,
+    /// The first day that counts towards the overtime balance.
+    pub balance_start: Option<NaiveDate>,
+    /// Currency, tax and billing increment of invoices.
+    pub invoice: InvoiceSettings,
+    /// The hourly rates per project.
+    pub rates: BTreeMap<String, HourlyRate>,
+    /// The consumed shares of a project budget (in percent) at which a warning is shown.
+    pub budget_alerts: Option<Vec<u32>>,
+    /// Whether `start` accepts projects that were neither tracked nor registered before.
+    pub unknown_projects: UnknownProjects,
+    /// Local git repositories whose commits `report --with-commits` lists for all projects
+    /// without a repository of their own.
+    pub repositories: Vec<PathBuf>,
+    /// How long work may be tracked before `stop`, `status` and `resume` ask whether it was
+    /// left running by accident.
+    #[serde(deserialize_with = "deserialize_opt_duration")]
+    pub idle_threshold: Option<Duration>,
+    /// Commands that are run when the tracking starts, stops, switches or pauses.
+    pub hooks: EventHooks,
+}
+
+const DEFAULT_BUDGET_ALERTS: [u32; 2] = [80, 100];
+const DEFAULT_IDLE_THRESHOLD_HOURS: i64 = 10;
+
+impl Config {
+    pub fn from_toml(content: &str) -> Result<Config, toml::de::Error> {
+        toml::from_str(content)
+    }
+
+    pub fn targets(&self) -> Option<Targets> {
+        Targets::new(&self.targets, self.daily_target)
+    }
+
+    pub fn idle_threshold(&self) -> Duration {
+        self.idle_threshold
+            .unwrap_or_else(|| Duration::hours(DEFAULT_IDLE_THRESHOLD_HOURS))
+    }
+
+    pub fn budget_alerts(&self) -> &[u32] {
+        self.budget_alerts
+            .as_deref()
+            .unwrap_or(&DEFAULT_BUDGET_ALERTS)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rounding::RoundingMode;
+
+    #[test]
+    fn parse_empty_config() {
+        let c = Config::from_toml("").unwrap();
+        assert!(c.round.is_none());
+    }
+
+    #[test]
+    fn parse_rounding() {
+        let c = Config::from_toml("round = \"15m:up\"").unwrap();
+        assert_eq!(c.round.unwrap().mode, RoundingMode::Up);
+    }
+
+    #[test]
+    fn parse_duration_format() {
+        let c = Config::from_toml("duration_format = \"iso8601\"").unwrap();
+        assert_eq!(c.duration_format, Some(DurationFormat::Iso8601));
+    }
+
+    #[test]
+    fn parse_daily_target() {
+        let c = Config::from_toml("daily_target = \"7h30m\"").unwrap();
+        assert_eq!(c.daily_target, Some(Duration::minutes(450)));
+    }
+
+    #[test]
+    fn parse_weekly_targets() {
+        let c = Config::from_toml(
+            "daily_target = \"8h\"\nbalance_start = \"2026-01-01\"\n[targets]\nfri = \"6h\"",
+        )
+        .unwrap();
+        assert_eq!(c.targets.fri, Some(Duration::hours(6)));
+        assert_eq!(c.balance_start, NaiveDate::from_ymd_opt(2026, 1, 1));
+        assert!(c.targets().is_some());
+        assert!(Config::from_toml("[targets]\nfriday = \"6h\"").is_err());
+    }
+
+    #[test]
+    fn parse_invoice_settings() {
+        let c = Config::from_toml(
+            "[invoice]\ncurrency = \"EUR\"\ntax_rate = 19\n[rates]\nfoo = 80\nbar = [{ rate = 90.5, from = \"2026-09-01\" }]",
+        )
+        .unwrap();
+        assert_eq!(c.invoice.currency.as_deref(), Some("EUR"));
+        assert_eq!(c.invoice.tax_rate, 19.0);
+        assert_eq!(c.rates.len(), 2);
+        assert!(Config::from_toml("[rates]\nfoo = \"a lot\"").is_err());
+    }
+
+    #[test]
+    fn parse_budget_alerts() {
+        assert_eq!(Config::from_toml("").unwrap().budget_alerts(), [80, 100]);
+        let c = Config::from_toml("budget_alerts = [50, 90]").unwrap();
+        assert_eq!(c.budget_alerts(), [50, 90]);
+        assert!(Config::from_toml("budget_alerts = []")
+            .unwrap()
+            .budget_alerts()
+            .is_empty());
+    }
+
+    #[test]
+    fn parse_unknown_projects() {
+        assert_eq!(Config::default().unknown_projects, UnknownProjects::Allow);
+        let c = Config::from_toml("unknown_projects = \"refuse\"").unwrap();
+        assert_eq!(c.unknown_projects, UnknownProjects::Refuse);
+        assert!(Config::from_toml("unknown_projects = \"ignore\"").is_err());
+    }
+
+    #[test]
+    fn parse_invalid_rounding() {
+        assert!(Config::from_toml("round = \"15m:sideways\"").is_err());
+    }
+}
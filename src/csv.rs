@@ -0,0 +1,144 @@
+use crate::TrackieError;
+use std::borrow::Cow;
+use std::io;
+use std::io::Write;
+
+/// A minimal writer for CSV files as described in RFC 4180.
+pub struct CsvWriter<W: Write> {
+    out: W,
+    delimiter: char,
+}
+
+impl<W: Write> CsvWriter<W> {
+    pub fn new(out: W, delimiter: char) -> Self {
+        CsvWriter { out, delimiter }
+    }
+
+    pub fn write_record<S: AsRef<str>>(&mut self, fields: &[S]) -> io::Result<()> {
+        let line: Vec<Cow<str>> = fields.iter().map(|f| self.escape(f.as_ref())).collect();
+        write!(self.out, "{}\r\n", line.join(&self.delimiter.to_string()))
+    }
+
+    fn escape<'a>(&self, field: &'a str) -> Cow<'a, str> {
+        let needs_quotes = field
+            .chars()
+            .any(|c| c == self.delimiter || c == '"' || c == '\r' || c == '\n');
+        match needs_quotes {
+            true => Cow::Owned(format!("\"{}\"", field.replace('"', "\"\""))),
+            false => Cow::Borrowed(field),
+        }
+    }
+}
+
+/// Parses a field delimiter given on the command line. Quotes and line breaks are refused, as
+/// they already have a meaning in CSV.
+pub fn parse_delimiter(input: &str) -> Result<char, TrackieError> {
+    let mut chars = input.chars();
+    match (chars.next(), chars.next()) {
+        (Some('"' | '\r' | '\n'), None) => Err(TrackieError::new(
+            "Quotes and line breaks cannot be used as delimiter.",
+        )),
+        (Some(c), None) => Ok(c),
+        _ => Err(TrackieError::new(
+            "The delimiter must be a single character.",
+        )),
+    }
+}
+
+/// Parses CSV content as described in RFC 4180 into its records.
+///
+/// Both CRLF and LF line endings are accepted. Returns [None] if a quoted field is not closed.
+pub fn parse_csv(content: &str, delimiter: char) -> Option<Vec<Vec<String>>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = content.trim_start_matches('\u{feff}').chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' if quoted => quoted = false,
+            '"' if field.is_empty() => quoted = true,
+            c if quoted => field.push(c),
+            c if c == delimiter => record.push(std::mem::take(&mut field)),
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            c => field.push(c),
+        }
+    }
+    if quoted {
+        return None;
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    Some(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_records() {
+        let records = parse_csv("a,b\r\n\"c,\"\"d\"\"\",\"multi\nline\"\nlast,", ',').unwrap();
+        assert_eq!(
+            records,
+            vec![
+                vec!["a", "b"],
+                vec!["c,\"d\"", "multi\nline"],
+                vec!["last", ""]
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_unclosed_quote() {
+        assert!(parse_csv("\"a,b", ',').is_none());
+    }
+
+    fn write(delimiter: char, fields: &[&str]) -> String {
+        let mut out = Vec::new();
+        CsvWriter::new(&mut out, delimiter)
+            .write_record(fields)
+            .unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn plain_fields() {
+        assert_eq!(write(',', &["a", "b", ""]), "a,b,\r\n");
+    }
+
+    #[test]
+    fn quoted_fields() {
+        assert_eq!(
+            write(',', &["a,b", "say \"hi\"", "multi\nline"]),
+            "\"a,b\",\"say \"\"hi\"\"\",\"multi\nline\"\r\n"
+        );
+    }
+
+    #[test]
+    fn parse_delimiters() {
+        assert_eq!(parse_delimiter(";").unwrap(), ';');
+        assert_eq!(parse_delimiter("\t").unwrap(), '\t');
+        assert!(parse_delimiter("\"").is_err());
+        assert!(parse_delimiter("\n").is_err());
+        assert!(parse_delimiter("\r").is_err());
+        assert!(parse_delimiter("").is_err());
+        assert!(parse_delimiter(";;").is_err());
+    }
+
+    #[test]
+    fn custom_delimiter() {
+        assert_eq!(write(';', &["a,b", "c;d"]), "a,b;\"c;d\"\r\n");
+    }
+}
@@ -0,0 +1,161 @@
+use crate::TrackieError;
+use serde::Deserialize;
+use std::fs::read_to_string;
+use std::path::{Path, PathBuf};
+
+/// The file that assigns a directory and all of its subdirectories to a project.
+pub const PROJECT_FILE: &str = ".trackie";
+
+/// A project inferred from the working directory.
+#[derive(Debug, PartialEq, Eq)]
+pub struct DetectedProject {
+    pub name: String,
+    pub tags: Vec<String>,
+}
+
+/// The content of a `.trackie` file.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ProjectFile {
+    /// The name of the project. Defaults to the name of the git repository.
+    project: Option<String>,
+    /// Tags added to everything tracked on the project.
+    tags: Vec<String>,
+    /// Tracks each branch of the git repository as a subproject, e.g. `trackie/fix-report`.
+    per_branch: bool,
+}
+
+/// Infers the project that `dir` belongs to. The nearest `.trackie` file takes precedence,
+/// otherwise the project is named after the git repository containing `dir`: the name of its
+/// `origin` remote or, without remotes, the name of its directory. With `per_branch`, the
+/// checked out branch is a subproject of it.
+pub fn detect_project(dir: &Path) -> Result<Option<DetectedProject>, TrackieError> {
+    let settings = match dir
+        .ancestors()
+        .map(|d| d.join(PROJECT_FILE))
+        .find(|f| f.is_file())
+    {
+        Some(file) => toml::from_str(&read_to_string(&file)?).map_err(|e| {
+            TrackieError::new(format!("Invalid {}: {}", file.display(), e).as_str())
+        })?,
+        None => ProjectFile::default(),
+    };
+    let root = dir.ancestors().find(|d| d.join(".git").exists());
+    let mut name = settings.project.or_else(|| root.and_then(repository_name));
+    if let (true, Some(branch)) = (settings.per_branch, root.and_then(current_branch)) {
+        name = name.map(|name| format!("{}/{}", name, branch));
+    }
+    Ok(name.map(|name| DetectedProject {
+        name,
+        tags: settings.tags,
+    }))
+}
+
+/// The name of the git repository in `root`, preferring the name of its remote.
+fn repository_name(root: &Path) -> Option<String> {
+    git_config(root)
+        .and_then(|config| remote_url(&config))
+        .and_then(|url| name_from_url(&url))
+        .or_else(|| Some(root.file_name()?.to_string_lossy().to_string()))
+}
+
+/// The git directory of the repository in `root`, following the `.git` file of worktrees.
+fn git_dir(root: &Path) -> Option<PathBuf> {
+    let git = root.join(".git");
+    if git.is_file() {
+        let content = read_to_string(&git).ok()?;
+        Some(root.join(content.trim().strip_prefix("gitdir:")?.trim()))
+    } else {
+        Some(git)
+    }
+}
+
+/// The branch checked out in `root`, if any.
+fn current_branch(root: &Path) -> Option<String> {
+    let head = read_to_string(git_dir(root)?.join("HEAD")).ok()?;
+    head.trim()
+        .strip_prefix("ref: refs/heads/")
+        .map(str::to_string)
+}
+
+/// Reads the config of the repository in `root`, which worktrees share with the main repository.
+fn git_config(root: &Path) -> Option<String> {
+    let mut git_dir = git_dir(root)?;
+    if let Ok(common_dir) = read_to_string(git_dir.join("commondir")) {
+        git_dir = git_dir.join(common_dir.trim());
+    }
+    read_to_string(git_dir.join("config")).ok()
+}
+
+/// The URL of the `origin` remote, or of the first remote if there is no `origin`.
+fn remote_url(config: &str) -> Option<String> {
+    let mut remotes: Vec<(String, String)> = Vec::new();
+    let mut section: Option<String> = None;
+    for line in config.lines().map(str::trim) {
+        if line.starts_with('[') {
+            section = line
+                .strip_prefix("[remote \"")
+                .and_then(|l| l.strip_suffix("\"]"))
+                .map(str::to_string);
+        } else if let (Some(remote), Some((key, value))) = (&section, line.split_once('=')) {
+            if key.trim() == "url" {
+                remotes.push((remote.clone(), value.trim().to_string()));
+            }
+        }
+    }
+    remotes
+        .iter()
+        .find(|(remote, _)| remote == "origin")
+        .or(remotes.first())
+        .map(|(_, url)| url.clone())
+}
+
+/// The name of a repository given by its URL, e.g. `trackie` for `git@github.com:beatbrot/trackie.git`.
+fn name_from_url(url: &str) -> Option<String> {
+    let name = url.trim_end_matches('/').rsplit([':', '/']).next()?;
+    let name = name.strip_suffix(".git").unwrap_or(name);
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_from_urls() {
+        let urls = [
+            "git@github.com:beatbrot/trackie.git",
+            "https://github.com/beatbrot/trackie",
+            "ssh://git@example.com:2222/group/trackie.git/",
+            "/srv/git/trackie.git",
+            "trackie",
+        ];
+        for url in urls {
+            assert_eq!(name_from_url(url).as_deref(), Some("trackie"), "{}", url);
+        }
+    }
+
+    #[test]
+    fn remote_urls() {
+        let config = r#"
+[core]
+	bare = false
+[remote "upstream"]
+	url = https://github.com/beatbrot/trackie.git
+	fetch = +refs/heads/*:refs/remotes/upstream/*
+[remote "origin"]
+	url = git@github.com:someone/fork.git
+[branch "main"]
+	remote = origin
+"#;
+        assert_eq!(
+            remote_url(config).as_deref(),
+            Some("git@github.com:someone/fork.git")
+        );
+        assert_eq!(
+            remote_url(&config.replace("\"origin\"", "\"mine\"")).as_deref(),
+            Some("https://github.com/beatbrot/trackie.git")
+        );
+        assert_eq!(remote_url("[core]\n\tbare = false\n"), None);
+    }
+}
@@ -0,0 +1,101 @@
+use crate::TrackieError;
+use chrono::Duration;
+use serde::{de, Deserialize, Deserializer, Serializer};
+
+/// Parses a human readable duration like `15m`, `8h` or `1h30m`.
+///
+/// Supported units are `d` (days), `h` (hours), `m` (minutes) and `s` (seconds).
+pub fn parse_duration(input: &str) -> Result<Duration, TrackieError> {
+    let invalid = || TrackieError::new(format!("Invalid duration: '{}'", input).as_str());
+
+    // Summed up in seconds with overflow checks, as `Duration` panics beyond its bounds.
+    let mut seconds: i64 = 0;
+    let mut number = String::new();
+    for c in input.trim().chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+        let value: i64 = number.parse().map_err(|_| invalid())?;
+        number.clear();
+        let unit = match c {
+            'd' => 24 * 3600,
+            'h' => 3600,
+            'm' => 60,
+            's' => 1,
+            _ => return Err(invalid()),
+        };
+        seconds = value
+            .checked_mul(unit)
+            .and_then(|v| seconds.checked_add(v))
+            .filter(|s| *s <= Duration::max_value().num_seconds())
+            .ok_or_else(invalid)?;
+    }
+
+    if !number.is_empty() || input.trim().is_empty() {
+        return Err(invalid());
+    }
+    Ok(Duration::seconds(seconds))
+}
+
+/// Deserializes an optional duration written like `8h`.
+pub fn deserialize_opt_duration<'de, D>(deserializer: D) -> Result<Option<Duration>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Option::<String>::deserialize(deserializer)?
+        .map(|s| parse_duration(&s).map_err(de::Error::custom))
+        .transpose()
+}
+
+/// Serializes a duration as a number of seconds.
+pub fn serialize_seconds<S>(d: &Duration, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.serialize_i64(d.num_seconds())
+}
+
+/// Deserializes a duration written as a number of seconds.
+pub fn deserialize_seconds<'de, D>(deserializer: D) -> Result<Duration, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    i64::deserialize(deserializer).map(Duration::seconds)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_single_unit() {
+        assert_eq!(parse_duration("15m").unwrap(), Duration::minutes(15));
+        assert_eq!(parse_duration("8h").unwrap(), Duration::hours(8));
+        assert_eq!(parse_duration("2d").unwrap(), Duration::days(2));
+    }
+
+    #[test]
+    fn parse_combined_units() {
+        assert_eq!(
+            parse_duration("1h30m").unwrap(),
+            Duration::hours(1) + Duration::minutes(30)
+        );
+    }
+
+    #[test]
+    fn parse_invalid() {
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("15").is_err());
+        assert!(parse_duration("m").is_err());
+        assert!(parse_duration("15x").is_err());
+    }
+
+    #[test]
+    fn parse_out_of_range() {
+        assert!(parse_duration("9999999999999999d").is_err());
+        assert!(parse_duration("99999999999999999999s").is_err());
+        assert!(parse_duration("9223372036854775807s1s").is_err());
+        assert!(parse_duration("106751991167d").is_ok());
+    }
+}
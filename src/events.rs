@@ -0,0 +1,299 @@
+use crate::time_log::{LogEntry, PendingLog, TimeLog};
+use chrono::{Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long a hook may run before it is killed, so that a hanging hook does not block trackie.
+const HOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The `[hooks]` table of the settings: shell commands that are run when the tracking changes.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EventHooks {
+    pub on_start: Vec<String>,
+    pub on_stop: Vec<String>,
+    pub on_switch: Vec<String>,
+    pub on_pause: Vec<String>,
+}
+
+impl EventHooks {
+    fn commands(&self, kind: EventKind) -> &[String] {
+        match kind {
+            EventKind::Start => &self.on_start,
+            EventKind::Stop => &self.on_stop,
+            EventKind::Switch => &self.on_switch,
+            EventKind::Pause => &self.on_pause,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EventKind {
+    /// Tracking started while nothing was tracked
+    Start,
+    /// Tracking stopped
+    Stop,
+    /// Tracking moved from one work log to another
+    Switch,
+    /// Tracking stopped with the intention to resume it
+    Pause,
+}
+
+impl EventKind {
+    fn name(&self) -> &'static str {
+        match self {
+            EventKind::Start => "start",
+            EventKind::Stop => "stop",
+            EventKind::Switch => "switch",
+            EventKind::Pause => "pause",
+        }
+    }
+}
+
+/// A change of the tracked work. Hooks receive it as JSON on stdin.
+#[derive(Debug, Serialize)]
+pub struct TrackingEvent {
+    pub event: EventKind,
+    /// The work tracked from now on, for `start` and `switch`.
+    pub started: Option<PendingLog>,
+    /// The work that ended, for `stop`, `switch` and `pause`.
+    pub stopped: Option<LogEntry>,
+}
+
+impl TrackingEvent {
+    /// The event that replaced the work tracked `before` by the one tracked in `log`, if any.
+    /// Changing the note or tags of the tracked work is no event.
+    pub fn between(before: Option<&PendingLog>, log: &TimeLog) -> Option<TrackingEvent> {
+        let after = log.pending.as_ref();
+        let event = match (before, after) {
+            (None, None) => return None,
+            (Some(b), Some(a)) if b.start == a.start && b.project_name == a.project_name => {
+                return None
+            }
+            (None, Some(_)) => EventKind::Start,
+            (Some(_), None) => EventKind::Stop,
+            (Some(_), Some(_)) => EventKind::Switch,
+        };
+        let stopped = before.map(|b| {
+            // The entry in the log knows the actual end, e.g. of a stop at an earlier time.
+            log.entries(b.start.date_naive()..=NaiveDate::MAX)
+                .map(|(_, e)| e)
+                .find(|e| e.start == b.start && e.project_name == b.project_name)
+                .cloned()
+                .unwrap_or_else(|| {
+                    LogEntry::from_time_log(b, after.map_or_else(Local::now, |a| a.start))
+                })
+        });
+        Some(TrackingEvent {
+            event,
+            started: after.cloned(),
+            stopped,
+        })
+    }
+
+    /// Reports a stop as a pause, e.g. when the tracking is meant to be resumed later.
+    pub fn into_pause(mut self) -> TrackingEvent {
+        if self.event == EventKind::Stop {
+            self.event = EventKind::Pause;
+        }
+        self
+    }
+
+    /// The environment variables describing the event, for hooks that do not read JSON. They
+    /// describe the started work on `start` and `switch`, and the stopped work otherwise.
+    fn env_vars(&self) -> Vec<(&'static str, String)> {
+        let mut vars = vec![("TRACKIE_EVENT", self.event.name().to_string())];
+        match (&self.started, &self.stopped) {
+            (Some(started), stopped) => {
+                vars.extend([
+                    ("TRACKIE_PROJECT", started.project_name.clone()),
+                    ("TRACKIE_START", started.start.to_rfc3339()),
+                    ("TRACKIE_NOTE", started.note.clone().unwrap_or_default()),
+                    ("TRACKIE_TAGS", started.tags.join(",")),
+                ]);
+                if let Some(stopped) = stopped {
+                    vars.push(("TRACKIE_PREVIOUS_PROJECT", stopped.project_name.clone()));
+                }
+            }
+            (None, Some(stopped)) => vars.extend([
+                ("TRACKIE_PROJECT", stopped.project_name.clone()),
+                ("TRACKIE_START", stopped.start.to_rfc3339()),
+                ("TRACKIE_END", stopped.end.to_rfc3339()),
+                (
+                    "TRACKIE_DURATION",
+                    stopped.to_duration().num_seconds().to_string(),
+                ),
+                ("TRACKIE_NOTE", stopped.note.clone().unwrap_or_default()),
+                ("TRACKIE_TAGS", stopped.tags.join(",")),
+            ]),
+            (None, None) => {}
+        }
+        vars
+    }
+}
+
+/// Runs the hooks configured for `event` one after another. Their output is only shown if they
+/// fail, and hooks that do not finish in time are killed. The log is already saved at this point,
+/// so failures only result in the returned warnings.
+pub fn run_hooks(hooks: &EventHooks, event: &TrackingEvent) -> Vec<String> {
+    let commands = hooks.commands(event.event);
+    if commands.is_empty() {
+        return Vec::new();
+    }
+    let json = serde_json::to_string(event).unwrap_or_default();
+    let vars = event.env_vars();
+    commands
+        .iter()
+        .filter_map(|command| {
+            run_hook(command, &vars, &json, HOOK_TIMEOUT)
+                .err()
+                .map(|e| format!("Hook `{}` failed: {}", command, e))
+        })
+        .collect()
+}
+
+fn run_hook(
+    command: &str,
+    vars: &[(&str, String)],
+    json: &str,
+    timeout: Duration,
+) -> Result<(), String> {
+    let mut child = shell(command)
+        .envs(vars.iter().map(|(k, v)| (k, v)))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| e.to_string())?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // Hooks that do not read the event close stdin early, which is fine.
+        let _ = writeln!(stdin, "{}", json);
+    }
+    // The error output is read on its own thread, so that a hook cannot block on a full pipe.
+    // Processes started in the background by the hook may keep the pipe open, so it is never
+    // waited for longer than the hook itself.
+    let (sender, receiver) = mpsc::channel();
+    if let Some(mut stderr) = child.stderr.take() {
+        thread::spawn(move || {
+            let mut output = String::new();
+            let _ = stderr.read_to_string(&mut output);
+            let _ = sender.send(output);
+        });
+    }
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        if let Some(status) = child.try_wait().map_err(|e| e.to_string())? {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(format!("timed out after {:?}", timeout));
+        }
+        thread::sleep(Duration::from_millis(10));
+    };
+    if status.success() {
+        return Ok(());
+    }
+    let stderr = receiver
+        .recv_timeout(deadline.saturating_duration_since(Instant::now()))
+        .unwrap_or_default();
+    match stderr.trim() {
+        "" => Err(status.to_string()),
+        stderr => Err(format!("{} ({})", stderr, status)),
+    }
+}
+
+#[cfg(unix)]
+fn shell(command: &str) -> Command {
+    let mut cmd = Command::new("sh");
+    cmd.arg("-c").arg(command);
+    cmd
+}
+
+#[cfg(windows)]
+fn shell(command: &str) -> Command {
+    let mut cmd = Command::new("cmd");
+    cmd.arg("/C").arg(command);
+    cmd
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detect_events() {
+        let mut log = TimeLog::default();
+        assert!(TrackingEvent::between(None, &log).is_none());
+
+        log.start_log("web").unwrap();
+        let event = TrackingEvent::between(None, &log).unwrap();
+        assert_eq!(event.event, EventKind::Start);
+        assert_eq!(event.started.unwrap().project_name, "web");
+        assert!(event.stopped.is_none());
+
+        let before = log.pending.clone();
+        log.pending.as_mut().unwrap().note = Some("Fix login".to_string());
+        assert!(TrackingEvent::between(before.as_ref(), &log).is_none());
+
+        let before = log.pending.clone();
+        log.start_log("api").unwrap();
+        let event = TrackingEvent::between(before.as_ref(), &log).unwrap();
+        assert_eq!(event.event, EventKind::Switch);
+        assert_eq!(event.stopped.as_ref().unwrap().project_name, "web");
+        let vars = event.env_vars();
+        let var = |name| vars.iter().find(|(n, _)| *n == name).map(|(_, v)| v);
+        assert_eq!(var("TRACKIE_PROJECT").unwrap(), "api");
+        assert_eq!(var("TRACKIE_PREVIOUS_PROJECT").unwrap(), "web");
+        assert_eq!(var("TRACKIE_NOTE").unwrap(), "");
+
+        let before = log.pending.clone();
+        log.stop_pending().unwrap();
+        let event = TrackingEvent::between(before.as_ref(), &log)
+            .unwrap()
+            .into_pause();
+        assert_eq!(event.event, EventKind::Pause);
+        assert_eq!(event.stopped.unwrap().project_name, "api");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn report_failed_hooks() {
+        let mut log = TimeLog::default();
+        log.start_log("web").unwrap();
+        let event = TrackingEvent::between(None, &log).unwrap();
+        let hooks = EventHooks {
+            on_start: vec![
+                "grep -q '\"event\":\"start\"' && test \"$TRACKIE_PROJECT\" = web".to_string(),
+                "echo 'no slack' >&2; exit 3".to_string(),
+            ],
+            on_stop: vec!["false".to_string()],
+            ..EventHooks::default()
+        };
+        assert_eq!(
+            run_hooks(&hooks, &event),
+            ["Hook `echo 'no slack' >&2; exit 3` failed: no slack (exit status: 3)"]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn kill_hanging_hooks() {
+        let start = Instant::now();
+        let result = run_hook("sleep 30", &[], "{}", Duration::from_millis(200));
+        assert_eq!(result, Err("timed out after 200ms".to_string()));
+        assert!(start.elapsed() < Duration::from_secs(5));
+
+        // A process left running in the background does not keep the hook from finishing.
+        let start = Instant::now();
+        let result = run_hook("sleep 30 & exit 2", &[], "{}", Duration::from_secs(1));
+        assert_eq!(result, Err("exit status: 2".to_string()));
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+}
@@ -0,0 +1,299 @@
+use crate::cli::CsvOptions;
+use crate::csv::CsvWriter;
+use crate::project::SEPARATORS;
+use crate::time_log::{LogEntry, TimeLog};
+use chrono::{DateTime, NaiveDate, SecondsFormat, TimeZone, Utc};
+use std::io;
+use std::io::Write;
+use std::ops::RangeInclusive;
+
+/// The format of the date and time in timeclock files.
+pub const TIMECLOCK_FORMAT: &str = "%Y/%m/%d %H:%M:%S";
+
+/// Writes one CSV row per [LogEntry](crate::time_log::LogEntry) logged on the given days.
+pub fn export_csv<W: Write>(
+    log: &TimeLog,
+    days: RangeInclusive<NaiveDate>,
+    options: &CsvOptions,
+    out: W,
+) -> io::Result<()> {
+    let mut w = CsvWriter::new(out, options.delimiter);
+    if !options.no_header {
+        w.write_record(&[
+            "date", "project", "start", "end", "duration", "note", "tags",
+        ])?;
+    }
+    for (date, e) in log.entries(days) {
+        w.write_record(&[
+            date.to_string(),
+            e.project_name.clone(),
+            e.start.to_rfc3339_opts(SecondsFormat::Secs, false),
+            e.end.to_rfc3339_opts(SecondsFormat::Secs, false),
+            e.to_duration().num_minutes().to_string(),
+            e.note.clone().unwrap_or_default(),
+            e.tags.join(","),
+        ])?;
+    }
+    Ok(())
+}
+
+/// Writes an iCalendar file with one VEVENT per [LogEntry] logged on the given days.
+///
+/// All times are written in UTC, so that calendar apps show them correctly regardless of the
+/// time zone they were tracked in.
+pub fn export_ics<W: Write>(
+    log: &TimeLog,
+    days: RangeInclusive<NaiveDate>,
+    mut out: W,
+) -> io::Result<()> {
+    let mut line = |l: &str| write!(out, "{}\r\n", fold_ics_line(l));
+    line("BEGIN:VCALENDAR")?;
+    line("VERSION:2.0")?;
+    line(concat!(
+        "PRODID:-//trackie//trackie ",
+        env!("CARGO_PKG_VERSION"),
+        "//EN"
+    ))?;
+    line("CALSCALE:GREGORIAN")?;
+    for (_, e) in log.entries(days) {
+        line("BEGIN:VEVENT")?;
+        line(&format!("UID:{}", ics_uid(e)))?;
+        line(&format!("DTSTAMP:{}", ics_time(&e.end)))?;
+        line(&format!("DTSTART:{}", ics_time(&e.start)))?;
+        line(&format!("DTEND:{}", ics_time(&e.end)))?;
+        line(&format!("SUMMARY:{}", escape_ics_text(&e.project_name)))?;
+        if let Some(note) = &e.note {
+            line(&format!("DESCRIPTION:{}", escape_ics_text(note)))?;
+        }
+        if !e.tags.is_empty() {
+            let tags: Vec<String> = e.tags.iter().map(|t| escape_ics_text(t)).collect();
+            line(&format!("CATEGORIES:{}", tags.join(",")))?;
+        }
+        line("END:VEVENT")?;
+    }
+    line("END:VCALENDAR")
+}
+
+/// Writes a timeclock file as read by ledger and hledger, with a pair of `i`/`o` lines per
+/// [LogEntry] logged on the given days.
+///
+/// The project is used as account with `:` between the levels of subprojects, the note as
+/// description and the tags are written as hledger tags in a comment. A `;` starts a comment,
+/// so it is replaced by `,` in accounts and descriptions. Times are written in local time as
+/// timeclock has no notion of time zones.
+pub fn export_timeclock<W: Write>(
+    log: &TimeLog,
+    days: RangeInclusive<NaiveDate>,
+    mut out: W,
+) -> io::Result<()> {
+    for (_, e) in log.entries(days) {
+        let mut clock_in = format!(
+            "i {} {}",
+            e.start.format(TIMECLOCK_FORMAT),
+            timeclock_account(&e.project_name)
+        );
+        if let Some(note) = &e.note {
+            clock_in.push_str("  ");
+            clock_in.push_str(&note.replace(['\r', '\n'], " ").replace(';', ","));
+        }
+        if !e.tags.is_empty() {
+            let tags: Vec<String> = e.tags.iter().map(|t| format!("{}:", t)).collect();
+            clock_in.push_str(&format!("  ; {}", tags.join(", ")));
+        }
+        writeln!(out, "{}", clock_in)?;
+        writeln!(out, "o {}", e.end.format(TIMECLOCK_FORMAT))?;
+    }
+    Ok(())
+}
+
+/// The ledger account for a project. Two spaces end an account, so any run of whitespace is
+/// collapsed into a single space, and `;` is replaced as it starts a comment.
+fn timeclock_account(project: &str) -> String {
+    project
+        .split(SEPARATORS)
+        .map(|level| {
+            level
+                .replace(';', ",")
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+fn ics_time<Tz: TimeZone>(t: &DateTime<Tz>) -> String {
+    t.with_timezone(&Utc).format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// Derives a UID from the start and the project of an entry, so that exporting the same entry
+/// twice leads to the same UID. Uses FNV-1a as its output is stable across Rust versions.
+fn ics_uid(e: &LogEntry) -> String {
+    let hash = e.project_name.bytes().fold(0xcbf29ce484222325_u64, |h, b| {
+        (h ^ b as u64).wrapping_mul(0x100000001b3)
+    });
+    format!("{}-{:016x}@trackie", ics_time(&e.start), hash)
+}
+
+fn escape_ics_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+/// Folds lines longer than 75 octets as required by RFC 5545.
+fn fold_ics_line(line: &str) -> String {
+    let mut result = String::with_capacity(line.len());
+    let mut line_len = 0;
+    for c in line.chars() {
+        if line_len + c.len_utf8() > 75 {
+            result.push_str("\r\n ");
+            line_len = 1;
+        }
+        result.push(c);
+        line_len += c.len_utf8();
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time_log::LogEntry;
+    use chrono::{Local, TimeZone};
+    use std::collections::BTreeMap;
+
+    fn log() -> TimeLog {
+        let date = NaiveDate::from_ymd_opt(2026, 9, 7).unwrap();
+        let entry = LogEntry {
+            project_name: "Foo, Inc.".to_string(),
+            start: Local.with_ymd_and_hms(2026, 9, 7, 9, 0, 0).unwrap(),
+            end: Local.with_ymd_and_hms(2026, 9, 7, 10, 30, 0).unwrap(),
+            note: Some("Said \"hi\"".to_string()),
+            tags: vec!["a".to_string(), "b".to_string()],
+        };
+        TimeLog::new_testing_only(BTreeMap::from([(date, vec![entry])]))
+    }
+
+    #[test]
+    fn export_ics_events() {
+        let mut out = Vec::new();
+        export_ics(&log(), NaiveDate::MIN..=NaiveDate::MAX, &mut out).unwrap();
+        let ics = String::from_utf8(out).unwrap();
+        let start = Local.with_ymd_and_hms(2026, 9, 7, 9, 0, 0).unwrap();
+
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+        assert!(ics.ends_with("END:VEVENT\r\nEND:VCALENDAR\r\n"));
+        assert!(ics.contains(&format!("DTSTART:{}\r\n", ics_time(&start))));
+        assert!(ics.contains("SUMMARY:Foo\\, Inc.\r\n"));
+        assert!(ics.contains("DESCRIPTION:Said \"hi\"\r\n"));
+        assert!(ics.contains("CATEGORIES:a,b\r\n"));
+    }
+
+    #[test]
+    fn ics_uid_is_stable() {
+        let lg = log();
+        let (_, entry) = lg.entries(NaiveDate::MIN..=NaiveDate::MAX).next().unwrap();
+        let uid = ics_uid(entry);
+
+        assert_eq!(uid, ics_uid(entry));
+        assert!(uid.ends_with("@trackie"));
+        assert!(uid.starts_with(&ics_time(&entry.start)));
+    }
+
+    #[test]
+    fn ics_time_is_utc() {
+        let t = chrono::FixedOffset::east_opt(2 * 3600)
+            .unwrap()
+            .with_ymd_and_hms(2026, 9, 7, 9, 0, 0)
+            .unwrap();
+        assert_eq!(ics_time(&t), "20260907T070000Z");
+    }
+
+    #[test]
+    fn escape_and_fold() {
+        assert_eq!(escape_ics_text("a;b,c\\d\ne"), "a\\;b\\,c\\\\d\\ne");
+
+        let folded = fold_ics_line(&"x".repeat(100));
+        let lines: Vec<&str> = folded.split("\r\n").collect();
+        assert_eq!(lines[0].len(), 75);
+        assert_eq!(lines[1], format!(" {}", "x".repeat(25)));
+    }
+
+    #[test]
+    fn export_timeclock_lines() {
+        let mut out = Vec::new();
+        export_timeclock(&log(), NaiveDate::MIN..=NaiveDate::MAX, &mut out).unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "i 2026/09/07 09:00:00 Foo, Inc.  Said \"hi\"  ; a:, b:\no 2026/09/07 10:30:00\n"
+        );
+
+        let date = NaiveDate::from_ymd_opt(2026, 9, 7).unwrap();
+        let entry = LogEntry {
+            project_name: "web;api".to_string(),
+            start: Local.with_ymd_and_hms(2026, 9, 7, 9, 0, 0).unwrap(),
+            end: Local.with_ymd_and_hms(2026, 9, 7, 10, 0, 0).unwrap(),
+            note: Some("Login; signup".to_string()),
+            tags: Vec::new(),
+        };
+        let lg = TimeLog::new_testing_only(BTreeMap::from([(date, vec![entry])]));
+        let mut out = Vec::new();
+        export_timeclock(&lg, NaiveDate::MIN..=NaiveDate::MAX, &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "i 2026/09/07 09:00:00 web,api  Login, signup\no 2026/09/07 10:00:00\n"
+        );
+    }
+
+    #[test]
+    fn timeclock_accounts() {
+        let mut lg = log();
+        lg.rename_project("Foo, Inc.", "client/web  site").unwrap();
+        let mut out = Vec::new();
+        export_timeclock(&lg, NaiveDate::MIN..=NaiveDate::MAX, &mut out).unwrap();
+        assert!(String::from_utf8(out)
+            .unwrap()
+            .starts_with("i 2026/09/07 09:00:00 client:web site  Said"));
+
+        assert_eq!(timeclock_account("client/website"), "client:website");
+        assert_eq!(timeclock_account("client:website"), "client:website");
+        assert_eq!(
+            timeclock_account("Foo  Inc./web\tsite"),
+            "Foo Inc.:web site"
+        );
+    }
+
+    fn export(options: CsvOptions) -> String {
+        let mut out = Vec::new();
+        export_csv(&log(), NaiveDate::MIN..=NaiveDate::MAX, &options, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn export_with_header() {
+        let csv = export(CsvOptions {
+            delimiter: ',',
+            no_header: false,
+        });
+        let lines: Vec<&str> = csv.split("\r\n").collect();
+
+        assert_eq!(lines[0], "date,project,start,end,duration,note,tags");
+        assert!(lines[1].starts_with("2026-09-07,\"Foo, Inc.\",2026-09-07T09:00:00"));
+        assert!(lines[1].ends_with(",90,\"Said \"\"hi\"\"\",\"a,b\""));
+    }
+
+    #[test]
+    fn export_without_header() {
+        let csv = export(CsvOptions {
+            delimiter: ';',
+            no_header: true,
+        });
+
+        assert!(csv.starts_with("2026-09-07;Foo, Inc.;"));
+        assert!(csv.ends_with(";90;\"Said \"\"hi\"\"\";a,b\r\n"));
+    }
+}
@@ -0,0 +1,119 @@
+use crate::TrackieError;
+use chrono::{DateTime, Local, TimeZone};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Separates the fields of a commit in the output of `git log`.
+const FIELD_SEPARATOR: char = '\x1f';
+
+/// A commit read from a local git repository.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Commit {
+    pub hash: String,
+    /// The time the commit was authored, not when it was committed, e.g. during a rebase.
+    pub time: DateTime<Local>,
+    pub subject: String,
+}
+
+impl Commit {
+    pub fn short_hash(&self) -> &str {
+        &self.hash[..self.hash.len().min(7)]
+    }
+}
+
+/// The commits of a repository, either linked to a project or used for all projects that have
+/// no repository of their own.
+#[derive(Debug)]
+pub struct RepositoryLog {
+    pub project: Option<String>,
+    pub commits: Vec<Commit>,
+}
+
+/// Reads the commits authored since `since` on any branch of the repository at `repo`. If the
+/// repository configures a `user.email`, only commits of that author are included.
+pub fn commits_since(repo: &Path, since: DateTime<Local>) -> Result<Vec<Commit>, TrackieError> {
+    let mut args = vec![
+        "log".to_string(),
+        "--all".to_string(),
+        "--no-merges".to_string(),
+        format!("--since={}", since.to_rfc3339()),
+        format!("--format=%H{0}%at{0}%s", FIELD_SEPARATOR),
+    ];
+    if let Some(email) = git(repo, &["config", "user.email"])
+        .ok()
+        .filter(|e| !e.is_empty())
+    {
+        args.push(format!("--author=<{}>", email));
+    }
+    let output = git(repo, &args.iter().map(String::as_str).collect::<Vec<_>>())?;
+    let commits = parse_log(&output);
+    Ok(commits.into_iter().filter(|c| c.time >= since).collect())
+}
+
+/// Runs git in `repo` and returns its trimmed output.
+pub fn git(repo: &Path, args: &[&str]) -> Result<String, TrackieError> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(args)
+        .output()
+        .map_err(|e| TrackieError::new(format!("Unable to run git: {}", e).as_str()))?;
+    if !output.status.success() {
+        return Err(TrackieError::new(
+            format!(
+                "git {} failed in {}: {}",
+                args.first().unwrap_or(&""),
+                repo.display(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            )
+            .as_str(),
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+fn parse_log(output: &str) -> Vec<Commit> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(3, FIELD_SEPARATOR);
+            let hash = fields.next()?.to_string();
+            let time = Local
+                .timestamp_opt(fields.next()?.parse().ok()?, 0)
+                .single()?;
+            let subject = fields.next().unwrap_or_default().to_string();
+            Some(Commit {
+                hash,
+                time,
+                subject,
+            })
+        })
+        .collect()
+}
+
+/// Replaces a leading `~` by the home directory.
+pub fn expand_home(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), dirs::home_dir()) {
+        (Ok(rest), Some(home)) => home.join(rest),
+        _ => path.to_path_buf(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_git_log() {
+        let output = "4e1f9c0a2b\u{1f}1700000000\u{1f}Fix report: rounding\n\
+                      a1b2c3d4e5f6a7b8\u{1f}1700003600\u{1f}\n\
+                      garbage";
+        let commits = parse_log(output);
+        assert_eq!(commits.len(), 2);
+        assert_eq!(commits[0].short_hash(), "4e1f9c0");
+        assert_eq!(commits[0].subject, "Fix report: rounding");
+        assert_eq!(commits[1].time.timestamp(), 1700003600);
+        assert_eq!(commits[1].subject, "");
+    }
+}
@@ -0,0 +1,229 @@
+use crate::config::Config;
+use crate::detect::detect_project;
+use crate::git::git;
+use crate::project::UnknownProjects;
+use crate::time_log::TimeLog;
+use crate::TrackieError;
+use clap::ValueEnum;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Marks the lines trackie added to a hook, so that they can be found again.
+const MARKER: &str = "# trackie: automatic time tracking";
+
+/// The git hooks that trackie installs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum GitHook {
+    /// Runs after a branch was checked out
+    PostCheckout,
+    /// Runs after a commit was created
+    PostCommit,
+}
+
+impl GitHook {
+    fn name(&self) -> &'static str {
+        match self {
+            GitHook::PostCheckout => "post-checkout",
+            GitHook::PostCommit => "post-commit",
+        }
+    }
+}
+
+/// Adds the trackie call to the hooks of the repository containing `repo`, keeping what the
+/// hooks already do. Existing hooks must be sh or bash scripts, as the call is a shell command.
+/// Returns the paths of the hooks that were changed.
+pub fn install_hooks(repo: &Path, trackie: &Path) -> Result<Vec<PathBuf>, TrackieError> {
+    let dir = hooks_dir(repo)?;
+    fs::create_dir_all(&dir)?;
+    let mut changes = Vec::new();
+    for hook in GitHook::value_variants() {
+        let path = dir.join(hook.name());
+        let content = fs::read_to_string(&path).unwrap_or_default();
+        if content.contains(MARKER) {
+            continue;
+        }
+        if !content.is_empty() && !is_shell_script(&content) {
+            return Err(TrackieError::new(
+                format!(
+                    "Cannot add trackie to {} as it is no sh or bash script. Call `{} hooks run {}` from it instead.",
+                    path.display(),
+                    trackie.display(),
+                    hook.name()
+                )
+                .as_str(),
+            ));
+        }
+        changes.push((hook, path, content));
+    }
+    // Nothing is written before all hooks were checked, so that no hook is left half-installed.
+    let mut installed = Vec::new();
+    for (hook, path, content) in changes {
+        let mut content = match content.is_empty() {
+            true => "#!/bin/sh\n".to_string(),
+            false => format!("{}\n", content.trim_end()),
+        };
+        // The output is discarded and failures are ignored, so that git is never disturbed.
+        content.push_str(&format!(
+            "{}\n{} hooks run {} \"$@\" >/dev/null 2>&1 || true\n",
+            MARKER,
+            shell_quote(&trackie.display().to_string()),
+            hook.name()
+        ));
+        fs::write(&path, content)?;
+        make_executable(&path)?;
+        installed.push(path);
+    }
+    Ok(installed)
+}
+
+/// Whether the shebang of a hook runs it with sh or bash, e.g. `#!/bin/sh` or
+/// `#!/usr/bin/env bash`.
+fn is_shell_script(content: &str) -> bool {
+    let Some(shebang) = content.lines().next().and_then(|l| l.strip_prefix("#!")) else {
+        return false;
+    };
+    let mut words = shebang.split_whitespace();
+    let name = |path: &str| path.rsplit('/').next().unwrap_or_default().to_string();
+    let interpreter = match words.next().map(name).as_deref() {
+        Some("env") => words.find(|w| !w.starts_with('-')).map(name),
+        other => other.map(String::from),
+    };
+    matches!(interpreter.as_deref(), Some("sh" | "bash"))
+}
+
+/// Quotes `s` for sh, so that it is passed as a single word without expanding anything.
+fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "'\\''"))
+}
+
+/// Removes the trackie call from the hooks of the repository containing `repo`. Hooks that do
+/// nothing else are deleted. Returns the paths of the hooks that were changed.
+pub fn uninstall_hooks(repo: &Path) -> Result<Vec<PathBuf>, TrackieError> {
+    let dir = hooks_dir(repo)?;
+    let mut removed = Vec::new();
+    for hook in GitHook::value_variants() {
+        let path = dir.join(hook.name());
+        let Ok(content) = fs::read_to_string(&path) else {
+            continue;
+        };
+        let Some(rest) = without_trackie(&content) else {
+            continue;
+        };
+        match rest
+            .lines()
+            .all(|l| l.trim().is_empty() || l.starts_with("#!"))
+        {
+            true => fs::remove_file(&path)?,
+            false => fs::write(&path, rest)?,
+        }
+        removed.push(path);
+    }
+    Ok(removed)
+}
+
+/// The content of a hook without the lines added by trackie, if there are any.
+fn without_trackie(content: &str) -> Option<String> {
+    let mut lines = content.lines();
+    let mut rest = Vec::new();
+    let mut found = false;
+    while let Some(line) = lines.next() {
+        if line == MARKER {
+            found = true;
+            lines.next();
+        } else {
+            rest.push(line);
+        }
+    }
+    found.then(|| format!("{}\n", rest.join("\n")))
+}
+
+fn hooks_dir(repo: &Path) -> Result<PathBuf, TrackieError> {
+    // Respects `core.hooksPath` and the hooks of worktrees.
+    let dir = git(repo, &["rev-parse", "--git-path", "hooks"])?;
+    Ok(repo.join(dir))
+}
+
+#[cfg(unix)]
+fn make_executable(path: &Path) -> Result<(), TrackieError> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(0o755))?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn make_executable(_: &Path) -> Result<(), TrackieError> {
+    Ok(())
+}
+
+/// Called by the installed hooks in `dir`: switches the tracking to the project of the
+/// repository or branch and, after a commit, adds its subject to the note of the tracked work.
+/// Returns whether the log was modified.
+pub fn run_hook(
+    log: &mut TimeLog,
+    hook: GitHook,
+    args: &[String],
+    dir: &Path,
+    config: &Config,
+) -> Result<bool, TrackieError> {
+    // The third argument of post-checkout is 0 if only files were checked out.
+    if hook == GitHook::PostCheckout && args.get(2).map(String::as_str) != Some("1") {
+        return Ok(false);
+    }
+    let Some(detected) = detect_project(dir)? else {
+        return Ok(false);
+    };
+    let project = log.projects.resolve(&detected.name).to_string();
+    if config.unknown_projects == UnknownProjects::Refuse && !log.is_known_project(&project) {
+        return Ok(false);
+    }
+    if log.pending.as_ref().map(|p| p.project_name.as_str()) != Some(project.as_str()) {
+        log.start_annotated_log(&project, None, detected.tags)?;
+    }
+    if hook == GitHook::PostCommit {
+        let subject = git(dir, &["log", "-1", "--format=%s"])?;
+        if let Some(pending) = log.pending.as_mut() {
+            pending.note = Some(match pending.note.take() {
+                Some(note) => format!("{}; {}", note, subject),
+                None => subject,
+            });
+        }
+    }
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detect_shell_scripts() {
+        assert!(is_shell_script("#!/bin/sh\nmake lint\n"));
+        assert!(is_shell_script("#! /bin/bash -e\n"));
+        assert!(is_shell_script("#!/usr/bin/env bash\n"));
+        assert!(!is_shell_script("#!/usr/bin/env python3\nimport sys\n"));
+        assert!(!is_shell_script("#!/usr/bin/fish\n"));
+        assert!(!is_shell_script("make lint\n"));
+    }
+
+    #[test]
+    fn quote_paths() {
+        assert_eq!(shell_quote("/usr/bin/trackie"), "'/usr/bin/trackie'");
+        assert_eq!(
+            shell_quote("/home/o'neil/$HOME `x`/trackie"),
+            "'/home/o'\\''neil/$HOME `x`/trackie'"
+        );
+    }
+
+    #[test]
+    fn remove_trackie_lines() {
+        let hook = format!(
+            "#!/bin/sh\nmake lint\n{}\n\"trackie\" hooks run post-commit \"$@\" || true\n",
+            MARKER
+        );
+        assert_eq!(
+            without_trackie(&hook).as_deref(),
+            Some("#!/bin/sh\nmake lint\n")
+        );
+        assert_eq!(without_trackie("#!/bin/sh\nmake lint\n"), None);
+    }
+}
@@ -0,0 +1,407 @@
+use crate::project::{is_valid_color, Projects};
+use crate::report_creator::DateRangeReport;
+use chrono::Duration;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::io;
+use std::io::Write;
+
+/// The colours of the projects in the charts. Projects without a colour of their own are assigned
+/// a colour by their name in alphabetical order, so that each project has the same colour in all
+/// charts.
+const PALETTE: [&str; 10] = [
+    "#4e79a7", "#f28e2b", "#e15759", "#76b7b2", "#59a14f", "#edc948", "#b07aa1", "#ff9da7",
+    "#9c755f", "#bab0ac",
+];
+
+pub const STYLE: &str = "\
+body { font-family: sans-serif; margin: 2em auto; max-width: 60em; color: #222; }
+h1 { font-size: 1.6em; }
+h2 { font-size: 1.3em; margin-top: 2em; }
+.charts { display: flex; flex-wrap: wrap; gap: 2em; align-items: flex-start; }
+.legend { list-style: none; padding: 0; }
+.legend li { margin: .3em 0; }
+.swatch { display: inline-block; width: 1em; height: 1em; margin-right: .5em; vertical-align: middle; }
+table { border-collapse: collapse; margin: 1em 0; min-width: 25em; }
+th, td { padding: .3em .8em; border-bottom: 1px solid #ddd; text-align: left; }
+td.duration, th.duration { text-align: right; font-variant-numeric: tabular-nums; }
+tfoot td { font-weight: bold; }
+svg text { font-size: 11px; fill: #555; }
+";
+
+const BAR_WIDTH: f64 = 24.0;
+const BAR_GAP: f64 = 12.0;
+const CHART_HEIGHT: f64 = 200.0;
+const AXIS_WIDTH: f64 = 40.0;
+const LABEL_HEIGHT: f64 = 60.0;
+const PIE_RADIUS: f64 = 100.0;
+
+/// Writes the report as a single HTML page with inline SVG charts and no external assets.
+pub fn write_html<W: Write>(
+    report: &DateRangeReport,
+    metadata: &Projects,
+    out: &mut W,
+) -> io::Result<()> {
+    let projects = project_totals(report);
+    let colors: BTreeMap<&str, &str> = projects
+        .keys()
+        .zip(PALETTE.iter().cycle())
+        .map(|(p, c)| {
+            let color = metadata
+                .get(p)
+                .and_then(|m| m.color.as_deref())
+                .filter(|c| is_valid_color(c));
+            (*p, color.unwrap_or(c))
+        })
+        .collect();
+    let format = |d: &Duration| report.duration_format.format(d);
+    let title = format!(
+        "Time report {} – {}",
+        report.range.start.format("%F"),
+        report.range.end.format("%F")
+    );
+
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+    let _ = writeln!(html, "<title>{}</title>", escape_html(&title));
+    let _ = writeln!(html, "<style>\n{}</style>\n</head>\n<body>", STYLE);
+    let _ = writeln!(html, "<h1>{}</h1>", escape_html(&title));
+    let _ = write!(
+        html,
+        "<p>Total: <strong>{}</strong>",
+        format(&report.total_duration)
+    );
+    if let Some(rounded) = report.rounded_duration {
+        let _ = write!(html, " (rounded: <strong>{}</strong>)", format(&rounded));
+    }
+    html.push_str("</p>\n");
+
+    if report.total_duration > Duration::zero() {
+        html.push_str("<div class=\"charts\">\n");
+        bar_chart(&mut html, report, &colors);
+        pie_chart(&mut html, &projects, &colors);
+        legend(&mut html, &projects, &colors, format);
+        html.push_str("</div>\n");
+    } else {
+        html.push_str("<p>No time tracked.</p>\n");
+    }
+
+    for day in &report.days {
+        let _ = writeln!(html, "<h2>{}</h2>", day.date.format("%a. %F"));
+        let rounded = report.rounded_duration.is_some();
+        html.push_str("<table>\n<thead><tr><th>Project</th><th class=\"duration\">Duration</th>");
+        if rounded {
+            html.push_str("<th class=\"duration\">Rounded</th>");
+        }
+        html.push_str("</tr></thead>\n<tbody>\n");
+        let row = |html: &mut String, cells: [String; 3]| {
+            let _ = write!(
+                html,
+                "<tr><td>{}</td><td class=\"duration\">{}</td>",
+                cells[0], cells[1]
+            );
+            if rounded {
+                let _ = write!(html, "<td class=\"duration\">{}</td>", cells[2]);
+            }
+            html.push_str("</tr>\n");
+        };
+        for p in day.leaves() {
+            let swatch = format!(
+                "<span class=\"swatch\" style=\"background: {}\"></span>",
+                colors[p.project.as_str()]
+            );
+            row(
+                &mut html,
+                [
+                    swatch + &escape_html(&p.project),
+                    format(&p.duration),
+                    p.rounded.map(|r| format(&r)).unwrap_or_default(),
+                ],
+            );
+        }
+        html.push_str("</tbody>\n<tfoot>\n");
+        let target = day
+            .target
+            .map(|t| format!(" / {}", format(&t)))
+            .unwrap_or_default();
+        row(
+            &mut html,
+            [
+                "Total".to_string(),
+                format(&day.total_duration) + &target,
+                day.rounded_duration.map(|r| format(&r)).unwrap_or_default(),
+            ],
+        );
+        html.push_str("</tfoot>\n</table>\n");
+    }
+
+    html.push_str("</body>\n</html>\n");
+    out.write_all(html.as_bytes())
+}
+
+fn project_totals(report: &DateRangeReport) -> BTreeMap<&str, Duration> {
+    let mut totals = BTreeMap::new();
+    for p in report.days.iter().flat_map(|d| d.leaves()) {
+        let total = totals
+            .entry(p.project.as_str())
+            .or_insert_with(Duration::zero);
+        *total = *total + p.duration;
+    }
+    totals
+}
+
+/// A bar per day, stacked by project, with a horizontal grid line per hour (or multiple hours
+/// for long days).
+fn bar_chart(html: &mut String, report: &DateRangeReport, colors: &BTreeMap<&str, &str>) {
+    let max_minutes = report
+        .days
+        .iter()
+        .map(|d| d.total_duration.num_minutes())
+        .max()
+        .unwrap_or_default()
+        .max(1);
+    let max_hours = (max_minutes + 59) / 60;
+    let step = (max_hours + 4) / 5;
+    let scale_hours = ((max_hours + step - 1) / step * step) as f64;
+    let y = |minutes: f64| CHART_HEIGHT - minutes / 60.0 / scale_hours * CHART_HEIGHT;
+
+    let width = AXIS_WIDTH + report.days.len() as f64 * (BAR_WIDTH + BAR_GAP);
+    let _ = writeln!(
+        html,
+        "<svg width=\"{:.0}\" height=\"{:.0}\" viewBox=\"0 0 {:.0} {:.0}\" role=\"img\" aria-label=\"Time per day\">",
+        width,
+        CHART_HEIGHT + LABEL_HEIGHT + 10.0,
+        width,
+        CHART_HEIGHT + LABEL_HEIGHT + 10.0
+    );
+    let _ = writeln!(html, "<g transform=\"translate(0 10)\">");
+    let mut hours = 0;
+    while hours as f64 <= scale_hours {
+        let line_y = y(hours as f64 * 60.0);
+        let _ = writeln!(
+            html,
+            "<line x1=\"{:.0}\" y1=\"{:.2}\" x2=\"{:.0}\" y2=\"{:.2}\" stroke=\"#ddd\"/>\
+             <text x=\"{:.0}\" y=\"{:.2}\" text-anchor=\"end\">{}h</text>",
+            AXIS_WIDTH - 4.0,
+            line_y,
+            width,
+            line_y,
+            AXIS_WIDTH - 8.0,
+            line_y + 4.0,
+            hours
+        );
+        hours += step;
+    }
+
+    for (i, day) in report.days.iter().enumerate() {
+        let x = AXIS_WIDTH + BAR_GAP / 2.0 + i as f64 * (BAR_WIDTH + BAR_GAP);
+        let mut stacked = 0.0;
+        for p in day.leaves() {
+            let minutes = p.duration.num_seconds() as f64 / 60.0;
+            let _ = writeln!(
+                html,
+                "<rect x=\"{:.2}\" y=\"{:.2}\" width=\"{:.0}\" height=\"{:.2}\" fill=\"{}\">\
+                 <title>{}: {} {}</title></rect>",
+                x,
+                y(stacked + minutes),
+                BAR_WIDTH,
+                y(stacked) - y(stacked + minutes),
+                colors[p.project.as_str()],
+                day.date.format("%F"),
+                escape_html(&p.project),
+                report.duration_format.format(&p.duration)
+            );
+            stacked += minutes;
+        }
+        let label_x = x + BAR_WIDTH / 2.0;
+        let _ = writeln!(
+            html,
+            "<text x=\"{:.2}\" y=\"{:.0}\" text-anchor=\"end\" transform=\"rotate(-45 {:.2} {:.0})\">{}</text>",
+            label_x,
+            CHART_HEIGHT + 14.0,
+            label_x,
+            CHART_HEIGHT + 14.0,
+            day.date.format("%a %d.%m.")
+        );
+    }
+    html.push_str("</g>\n</svg>\n");
+}
+
+/// A slice per project, proportional to its share of the total time.
+fn pie_chart(
+    html: &mut String,
+    projects: &BTreeMap<&str, Duration>,
+    colors: &BTreeMap<&str, &str>,
+) {
+    let size = 2.0 * PIE_RADIUS;
+    let _ = writeln!(
+        html,
+        "<svg width=\"{:.0}\" height=\"{:.0}\" viewBox=\"0 0 {:.0} {:.0}\" role=\"img\" aria-label=\"Time per project\">",
+        size, size, size, size
+    );
+    let total: f64 = projects.values().map(|d| d.num_seconds() as f64).sum();
+    let point = |fraction: f64| {
+        let angle = fraction * std::f64::consts::TAU - std::f64::consts::FRAC_PI_2;
+        (
+            PIE_RADIUS + PIE_RADIUS * angle.cos(),
+            PIE_RADIUS + PIE_RADIUS * angle.sin(),
+        )
+    };
+
+    let mut start = 0.0;
+    for (project, duration) in projects {
+        let share = duration.num_seconds() as f64 / total;
+        let title = format!(
+            "<title>{}: {:.0}%</title>",
+            escape_html(project),
+            share * 100.0
+        );
+        if share >= 1.0 {
+            // An arc cannot start and end at the same point, so a single project is a circle.
+            let _ = writeln!(
+                html,
+                "<circle cx=\"{r:.0}\" cy=\"{r:.0}\" r=\"{r:.0}\" fill=\"{}\">{}</circle>",
+                colors[project],
+                title,
+                r = PIE_RADIUS
+            );
+        } else if share > 0.0 {
+            let (x0, y0) = point(start);
+            let (x1, y1) = point(start + share);
+            let _ = writeln!(
+                html,
+                "<path d=\"M{r:.0},{r:.0} L{:.2},{:.2} A{r:.0},{r:.0} 0 {} 1 {:.2},{:.2} Z\" fill=\"{}\">{}</path>",
+                x0,
+                y0,
+                u8::from(share > 0.5),
+                x1,
+                y1,
+                colors[project],
+                title,
+                r = PIE_RADIUS
+            );
+        }
+        start += share;
+    }
+    html.push_str("</svg>\n");
+}
+
+fn legend(
+    html: &mut String,
+    projects: &BTreeMap<&str, Duration>,
+    colors: &BTreeMap<&str, &str>,
+    format: impl Fn(&Duration) -> String,
+) {
+    html.push_str("<ul class=\"legend\">\n");
+    for (project, duration) in projects {
+        let _ = writeln!(
+            html,
+            "<li><span class=\"swatch\" style=\"background: {}\"></span>{} ({})</li>",
+            colors[project],
+            escape_html(project),
+            format(duration)
+        );
+    }
+    html.push_str("</ul>\n");
+}
+
+pub fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report_creator::ReportCreator;
+    use crate::time_log::{LogEntry, TimeLog};
+    use chrono::{Local, NaiveDate, TimeZone};
+    use std::collections::BTreeMap;
+
+    fn entry(day: u32, minutes: i64, project: &str) -> LogEntry {
+        let start = Local.with_ymd_and_hms(2026, 9, day, 9, 0, 0).unwrap();
+        LogEntry {
+            project_name: project.to_string(),
+            start,
+            end: start + Duration::minutes(minutes),
+            note: None,
+            tags: Vec::new(),
+        }
+    }
+
+    fn render(log: &TimeLog) -> String {
+        #[allow(deprecated)]
+        let date = Local.ymd(2026, 9, 8);
+        let report = ReportCreator::new(log).report_days(date, 2, true);
+        let mut out = Vec::new();
+        write_html(&report, &log.projects, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    fn log() -> TimeLog {
+        let monday = NaiveDate::from_ymd_opt(2026, 9, 7).unwrap();
+        let tuesday = monday.succ_opt().unwrap();
+        TimeLog::new_testing_only(BTreeMap::from([
+            (monday, vec![entry(7, 90, "Foo"), entry(7, 30, "<Bar>")]),
+            (tuesday, vec![entry(8, 60, "Foo")]),
+        ]))
+    }
+
+    #[test]
+    fn html_is_self_contained() {
+        let html = render(&log());
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.ends_with("</html>\n"));
+        assert!(!html.contains("src="));
+        assert!(!html.contains("href="));
+        assert!(!html.contains("<script"));
+    }
+
+    #[test]
+    fn html_contains_charts_and_tables() {
+        let html = render(&log());
+
+        // One bar segment per project and day and one slice per project
+        assert_eq!(html.matches("<rect").count(), 3);
+        assert_eq!(html.matches("<path").count(), 2);
+        assert_eq!(html.matches("<table>").count(), 2);
+        assert!(html.contains("<h2>Mon. 2026-09-07</h2>"));
+        assert!(html.contains("Total: <strong>03h 00m</strong>"));
+        assert!(html.contains("&lt;Bar&gt;: 17%"));
+        assert!(!html.contains("<Bar>"));
+    }
+
+    #[test]
+    fn custom_project_colors() {
+        let mut log = log();
+        log.projects.get_or_insert("Foo").color = Some("teal".to_string());
+        log.projects.get_or_insert("<Bar>").color = Some("\"><script>".to_string());
+        let html = render(&log);
+
+        assert!(html.contains("\"teal\""));
+        assert!(html.contains(PALETTE[0]));
+        assert!(!html.contains("<script"));
+    }
+
+    #[test]
+    fn single_project_is_a_circle() {
+        let monday = NaiveDate::from_ymd_opt(2026, 9, 7).unwrap();
+        let log = TimeLog::new_testing_only(BTreeMap::from([(monday, vec![entry(7, 60, "Foo")])]));
+        let html = render(&log);
+
+        assert_eq!(html.matches("<circle").count(), 1);
+        assert_eq!(html.matches("<path").count(), 0);
+    }
+
+    #[test]
+    fn empty_report() {
+        let html = render(&TimeLog::new());
+
+        assert!(html.contains("No time tracked."));
+        assert!(!html.contains("<svg"));
+    }
+}
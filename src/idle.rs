@@ -0,0 +1,151 @@
+use crate::pretty_string::DurationFormat;
+use crate::time_log::{LogEntry, PendingLog};
+use crate::TrackieError;
+use chrono::{DateTime, Duration, Local, NaiveTime, TimeZone};
+use clap::ValueEnum;
+use std::io::{self, BufRead, Write};
+
+/// How tracked work that has been running for suspiciously long is handled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum IdlePolicy {
+    /// Keeps the tracked time as it is
+    Keep,
+    /// Ends the tracking when the work ended
+    Trim,
+    /// Logs the time until the work ended and tracks again from now on, leaving out the time in between
+    Split,
+    /// Drops the tracked time
+    Discard,
+}
+
+/// What happened to tracked work that has been running for suspiciously long.
+pub enum IdleOutcome {
+    /// The work was not idle or is kept as it is.
+    Unchanged,
+    /// The work ended earlier. After a split, it is tracked again from now on.
+    Trimmed(LogEntry),
+    /// The work was dropped.
+    Discarded,
+}
+
+/// Parses a time of day like `12:30`.
+pub fn parse_time(input: &str) -> Result<NaiveTime, TrackieError> {
+    NaiveTime::parse_from_str(input.trim(), "%H:%M")
+        .map_err(|_| TrackieError::new(format!("Invalid time: '{}'. Use HH:MM.", input).as_str()))
+}
+
+/// The last point in time before `now` at which the clock showed `time`. It must lie after
+/// `start`, as the work cannot end before it started.
+pub fn end_at(
+    time: NaiveTime,
+    start: DateTime<Local>,
+    now: DateTime<Local>,
+) -> Result<DateTime<Local>, TrackieError> {
+    let mut date = now.date_naive();
+    while date >= start.date_naive() {
+        let end = Local.from_local_datetime(&date.and_time(time)).earliest();
+        match end {
+            Some(end) if end <= now && end > start => return Ok(end),
+            Some(end) if end <= start => break,
+            _ => {}
+        }
+        match date.pred_opt() {
+            Some(pred) => date = pred,
+            None => break,
+        }
+    }
+    Err(TrackieError::new(
+        format!(
+            "{} is not between the start of the tracking and now.",
+            time.format("%H:%M")
+        )
+        .as_str(),
+    ))
+}
+
+/// Asks the user how to handle the idle `pending` work, suggesting `default_end` as the time at
+/// which the work ended.
+pub fn ask_idle_policy(
+    pending: &PendingLog,
+    default_end: DateTime<Local>,
+    format: DurationFormat,
+) -> Result<(IdlePolicy, DateTime<Local>), TrackieError> {
+    let now = Local::now();
+    let mut stdin = io::stdin().lock();
+    let mut prompt = |question: &str| -> Result<String, TrackieError> {
+        eprint!("{}", question);
+        io::stderr().flush()?;
+        let mut answer = String::new();
+        if stdin.read_line(&mut answer)? == 0 {
+            return Err(TrackieError::new("No answer given."));
+        }
+        Ok(answer.trim().to_lowercase())
+    };
+
+    eprintln!(
+        "Project {} has been tracked for {} since {}.",
+        pending.project_name,
+        format.format(&(now - pending.start)),
+        pending.start.format("%a. %H:%M")
+    );
+    let policy = loop {
+        match prompt("[k]eep, [t]rim, [s]plit or [d]iscard it? ")?.as_str() {
+            "k" | "keep" => break IdlePolicy::Keep,
+            "t" | "trim" => break IdlePolicy::Trim,
+            "s" | "split" => break IdlePolicy::Split,
+            "d" | "discard" => break IdlePolicy::Discard,
+            _ => {}
+        }
+    };
+    if !matches!(policy, IdlePolicy::Trim | IdlePolicy::Split) {
+        return Ok((policy, default_end));
+    }
+    loop {
+        let answer = prompt(&format!(
+            "When did the work end? [{}] ",
+            default_end.format("%H:%M")
+        ))?;
+        if answer.is_empty() {
+            return Ok((policy, default_end));
+        }
+        match parse_time(&answer).and_then(|time| end_at(time, pending.start, now)) {
+            Ok(end) => return Ok((policy, end)),
+            Err(e) => eprintln!("{}", e),
+        }
+    }
+}
+
+/// Whether `pending` has been running for longer than `threshold`.
+pub fn is_idle(pending: &PendingLog, threshold: Duration) -> bool {
+    pending.get_pending_duration() > threshold
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn end_times() {
+        let local = |d: u32, h: u32, m: u32| {
+            Local
+                .from_local_datetime(
+                    &chrono::NaiveDate::from_ymd_opt(2026, 10, d)
+                        .unwrap()
+                        .and_hms_opt(h, m, 0)
+                        .unwrap(),
+                )
+                .unwrap()
+        };
+        let time = |t: &str| parse_time(t).unwrap();
+        let (start, now) = (local(15, 17, 0), local(16, 9, 30));
+
+        assert_eq!(
+            end_at(time("18:45"), start, now).unwrap(),
+            local(15, 18, 45)
+        );
+        assert_eq!(end_at(time("8:15"), start, now).unwrap(), local(16, 8, 15));
+        assert!(end_at(time("16:00"), start, now).is_err());
+        assert!(end_at(time("09:45"), local(16, 9, 0), now).is_err());
+        assert!(parse_time("9h").is_err());
+    }
+}
@@ -0,0 +1,524 @@
+use crate::csv::parse_csv;
+use crate::export::TIMECLOCK_FORMAT;
+use crate::time_log::{LogEntry, TimeLog};
+use crate::TrackieError;
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use clap::ValueEnum;
+use serde::de::IgnoredAny;
+use serde::Deserialize;
+
+/// The tools whose data can be imported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ImportSource {
+    /// A detailed report exported from Toggl Track as CSV
+    TogglCsv,
+    /// Watson's `frames` file
+    Watson,
+    /// A Timewarrior data file (`YYYY-MM.data`) or the output of `timew export`
+    Timewarrior,
+    /// A timeclock file as used by ledger and hledger
+    Timeclock,
+}
+
+/// The outcome of an import.
+pub struct ImportSummary {
+    pub imported: Vec<LogEntry>,
+    pub duplicates: usize,
+}
+
+/// Parses the content of a file exported by another tool into entries.
+pub fn parse_entries(source: ImportSource, content: &str) -> Result<Vec<LogEntry>, TrackieError> {
+    match source {
+        ImportSource::TogglCsv => parse_toggl_csv(content),
+        ImportSource::Watson => parse_watson(content),
+        ImportSource::Timewarrior => parse_timewarrior(content),
+        ImportSource::Timeclock => parse_timeclock(content),
+    }
+}
+
+/// Adds all entries to the log that it does not already contain.
+pub fn import_entries(log: &mut TimeLog, entries: Vec<LogEntry>) -> ImportSummary {
+    let mut summary = ImportSummary {
+        imported: Vec::new(),
+        duplicates: 0,
+    };
+    for entry in entries {
+        if log.contains(&entry) {
+            summary.duplicates += 1;
+        } else {
+            log.add_entry(entry.clone());
+            summary.imported.push(entry);
+        }
+    }
+    summary
+}
+
+fn invalid(source: &str, detail: &str) -> TrackieError {
+    TrackieError::new(format!("Invalid {} data: {}", source, detail).as_str())
+}
+
+/// Rejects entries that do not end after they start, as they would count as negative time.
+fn checked(entry: LogEntry, source: &str, position: &str) -> Result<LogEntry, TrackieError> {
+    match entry.end > entry.start {
+        true => Ok(entry),
+        false => Err(invalid(
+            source,
+            format!("{}: the entry does not end after it starts", position).as_str(),
+        )),
+    }
+}
+
+fn parse_toggl_csv(content: &str) -> Result<Vec<LogEntry>, TrackieError> {
+    let records = parse_csv(content, ',').ok_or_else(|| invalid("Toggl", "unclosed quote"))?;
+    let mut records = records.into_iter();
+    let header = records
+        .next()
+        .ok_or_else(|| invalid("Toggl", "missing header"))?;
+    let column = |name: &str| {
+        header
+            .iter()
+            .position(|h| h == name)
+            .ok_or_else(|| invalid("Toggl", format!("missing column '{}'", name).as_str()))
+    };
+    let (project, description, tags) =
+        (column("Project")?, column("Description")?, column("Tags")?);
+    let (start_date, start_time) = (column("Start date")?, column("Start time")?);
+    let (end_date, end_time) = (column("End date")?, column("End time")?);
+
+    let local_time = |date: &str, time: &str| -> Result<DateTime<Local>, TrackieError> {
+        let date = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .map_err(|_| invalid("Toggl", format!("invalid date '{}'", date).as_str()))?;
+        let time = NaiveTime::parse_from_str(time, "%H:%M:%S")
+            .map_err(|_| invalid("Toggl", format!("invalid time '{}'", time).as_str()))?;
+        Local
+            .from_local_datetime(&date.and_time(time))
+            .earliest()
+            .ok_or_else(|| invalid("Toggl", "non-existent local time"))
+    };
+
+    records
+        .enumerate()
+        .filter(|(_, r)| r.iter().any(|f| !f.is_empty()))
+        .map(|(n, r)| {
+            let field = |i: usize| r.get(i).map(String::as_str).unwrap_or_default();
+            let entry = LogEntry {
+                // Toggl allows entries without a project, which are tracked under a fixed one.
+                project_name: match field(project).trim() {
+                    "" => "toggl".to_string(),
+                    project => project.to_string(),
+                },
+                start: local_time(field(start_date), field(start_time))?,
+                end: local_time(field(end_date), field(end_time))?,
+                note: Some(field(description).to_string()).filter(|d| !d.is_empty()),
+                tags: field(tags)
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|t| !t.is_empty())
+                    .map(String::from)
+                    .collect(),
+            };
+            // The header is the first row.
+            checked(entry, "Toggl", format!("row {}", n + 2).as_str())
+        })
+        .collect()
+}
+
+/// A frame of Watson: `[start, stop, project, id, tags, updated_at]`.
+#[derive(Deserialize)]
+struct WatsonFrame(i64, i64, String, IgnoredAny, #[serde(default)] Vec<String>);
+
+fn parse_watson(content: &str) -> Result<Vec<LogEntry>, TrackieError> {
+    let frames: Vec<serde_json::Value> =
+        serde_json::from_str(content).map_err(|e| invalid("Watson", &e.to_string()))?;
+    let timestamp = |ts: i64| {
+        Local
+            .timestamp_opt(ts, 0)
+            .single()
+            .ok_or_else(|| invalid("Watson", format!("invalid timestamp {}", ts).as_str()))
+    };
+
+    frames
+        .into_iter()
+        .enumerate()
+        .map(|(n, f)| {
+            // Watson appends further fields to frames over time, so only the known ones are read.
+            let known: Vec<serde_json::Value> = f
+                .as_array()
+                .into_iter()
+                .flatten()
+                .take(5)
+                .cloned()
+                .collect();
+            let frame: WatsonFrame = serde_json::from_value(serde_json::Value::Array(known))
+                .map_err(|e| invalid("Watson", &e.to_string()))?;
+            let entry = LogEntry {
+                project_name: frame.2,
+                start: timestamp(frame.0)?,
+                end: timestamp(frame.1)?,
+                note: None,
+                tags: frame.4,
+            };
+            checked(entry, "Watson", format!("frame {}", n + 1).as_str())
+        })
+        .collect()
+}
+
+/// An interval as written by `timew export`.
+#[derive(Deserialize)]
+struct TimewarriorInterval {
+    start: String,
+    end: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+    annotation: Option<String>,
+}
+
+fn parse_timewarrior(content: &str) -> Result<Vec<LogEntry>, TrackieError> {
+    // Each interval is paired with its position for error messages.
+    let intervals: Vec<(String, TimewarriorInterval)> = match content.trim_start().starts_with('[')
+    {
+        true => serde_json::from_str::<Vec<TimewarriorInterval>>(content)
+            .map_err(|e| invalid("Timewarrior", &e.to_string()))?
+            .into_iter()
+            .enumerate()
+            .map(|(n, i)| (format!("interval {}", n + 1), i))
+            .collect(),
+        false => content
+            .lines()
+            .enumerate()
+            .filter(|(_, l)| !l.trim().is_empty())
+            .map(|(n, l)| Ok((format!("line {}", n + 1), parse_timewarrior_line(l)?)))
+            .collect::<Result<Vec<_>, TrackieError>>()?,
+    };
+
+    let utc = |t: &str| {
+        NaiveDateTime::parse_from_str(t, "%Y%m%dT%H%M%SZ")
+            .map(|t| Utc.from_utc_datetime(&t).with_timezone(&Local))
+            .map_err(|_| invalid("Timewarrior", format!("invalid time '{}'", t).as_str()))
+    };
+
+    intervals
+        .into_iter()
+        // Open intervals are still being tracked by Timewarrior and are skipped.
+        .filter_map(|(position, i)| i.end.clone().map(|end| (position, i, end)))
+        .map(|(position, i, end)| {
+            // Timewarrior has no projects, so the first tag is used as project.
+            let mut tags = i.tags.into_iter();
+            let entry = LogEntry {
+                project_name: tags.next().unwrap_or_else(|| "timewarrior".to_string()),
+                start: utc(&i.start)?,
+                end: utc(&end)?,
+                note: i.annotation.filter(|a| !a.is_empty()),
+                tags: tags.collect(),
+            };
+            checked(entry, "Timewarrior", &position)
+        })
+        .collect()
+}
+
+/// Parses a line like `inc 20260907T070000Z - 20260907T083000Z # tag "other tag" # "annotation"`.
+fn parse_timewarrior_line(line: &str) -> Result<TimewarriorInterval, TrackieError> {
+    let mut sections = line.splitn(3, " # ");
+    let range: Vec<&str> = sections
+        .next()
+        .unwrap_or_default()
+        .split_whitespace()
+        .collect();
+    let (start, end) = match range.as_slice() {
+        ["inc", start] => (start.to_string(), None),
+        ["inc", start, "-", end] => (start.to_string(), Some(end.to_string())),
+        _ => {
+            return Err(invalid(
+                "Timewarrior",
+                format!("invalid line '{}'", line).as_str(),
+            ))
+        }
+    };
+    Ok(TimewarriorInterval {
+        start,
+        end,
+        tags: sections.next().map(split_quoted).unwrap_or_default(),
+        annotation: sections
+            .next()
+            .map(|a| a.trim().trim_matches('"').replace("\\\"", "\"")),
+    })
+}
+
+/// Splits at whitespace, keeping quoted words together.
+fn split_quoted(s: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut quoted = false;
+    for c in s.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            c if c.is_whitespace() && !quoted => {
+                if !word.is_empty() {
+                    words.push(std::mem::take(&mut word));
+                }
+            }
+            c => word.push(c),
+        }
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words
+}
+
+fn parse_timeclock(content: &str) -> Result<Vec<LogEntry>, TrackieError> {
+    let mut entries = Vec::new();
+    let mut clocked_in: Option<LogEntry> = None;
+
+    for (n, line) in content.lines().enumerate() {
+        let error =
+            |detail: &str| invalid("timeclock", format!("line {}: {}", n + 1, detail).as_str());
+        let (code, rest) = split_token(line);
+        match code {
+            "i" => {
+                if clocked_in.is_some() {
+                    return Err(error("clock-in without clock-out"));
+                }
+                let (start, rest) =
+                    parse_timeclock_time(rest).ok_or_else(|| error("invalid time"))?;
+                let (rest, comment) = match rest.find(';') {
+                    Some(i) => (&rest[..i], &rest[i + 1..]),
+                    None => (rest, ""),
+                };
+                let (project, note) = match rest.trim().split_once("  ") {
+                    Some((project, note)) => (project, Some(note.trim().to_string())),
+                    None => (rest.trim(), None),
+                };
+                if project.is_empty() {
+                    return Err(error("missing account"));
+                }
+                clocked_in = Some(LogEntry {
+                    project_name: project.to_string(),
+                    start,
+                    end: start,
+                    note,
+                    tags: comment
+                        .split(',')
+                        .filter_map(|t| t.split_once(':'))
+                        .filter_map(|(name, _)| name.split_whitespace().last())
+                        .map(String::from)
+                        .collect(),
+                });
+            }
+            "o" | "O" => {
+                let (end, _) = parse_timeclock_time(rest).ok_or_else(|| error("invalid time"))?;
+                let mut entry = clocked_in
+                    .take()
+                    .ok_or_else(|| error("clock-out without clock-in"))?;
+                entry.end = end;
+                entries.push(checked(
+                    entry,
+                    "timeclock",
+                    format!("line {}", n + 1).as_str(),
+                )?);
+            }
+            // Comments, empty lines and other directives
+            _ => {}
+        }
+    }
+    // A remaining clock-in is still being tracked and is skipped, like open Timewarrior intervals.
+    Ok(entries)
+}
+
+/// Parses the date and time at the start of a timeclock line, e.g. `2026/09/07 09:00[:00]`.
+fn parse_timeclock_time(s: &str) -> Option<(DateTime<Local>, &str)> {
+    let (date, rest) = split_token(s);
+    let (time, rest) = split_token(rest);
+    let date_time = format!("{} {}", date.replace(['-', '.'], "/"), time);
+    let naive = NaiveDateTime::parse_from_str(&date_time, TIMECLOCK_FORMAT)
+        .or_else(|_| NaiveDateTime::parse_from_str(&date_time, "%Y/%m/%d %H:%M"))
+        .ok()?;
+    Some((Local.from_local_datetime(&naive).earliest()?, rest))
+}
+
+/// Splits off the first whitespace separated word.
+fn split_token(s: &str) -> (&str, &str) {
+    let s = s.trim_start();
+    s.split_at(s.find(char::is_whitespace).unwrap_or(s.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Timelike;
+
+    fn utc(h: u32, m: u32) -> DateTime<Local> {
+        Utc.with_ymd_and_hms(2026, 9, 7, h, m, 0)
+            .unwrap()
+            .with_timezone(&Local)
+    }
+
+    #[test]
+    fn parse_toggl() {
+        let entries = parse_entries(
+            ImportSource::TogglCsv,
+            include_str!("../tests/fixtures/toggl.csv"),
+        )
+        .unwrap();
+
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].project_name, "Website");
+        assert_eq!(entries[0].note.as_deref(), Some("Landing page, hero"));
+        assert_eq!(entries[0].tags, vec!["design", "frontend"]);
+        assert_eq!(entries[0].start.hour(), 9);
+        assert_eq!(entries[0].to_duration().num_minutes(), 90);
+        assert!(entries[1].note.is_some());
+        assert!(entries[1].tags.is_empty());
+        assert_eq!(entries[2].project_name, "toggl");
+    }
+
+    #[test]
+    fn parse_watson_frames() {
+        let entries = parse_entries(
+            ImportSource::Watson,
+            include_str!("../tests/fixtures/watson-frames.json"),
+        )
+        .unwrap();
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].project_name, "website");
+        assert_eq!(entries[0].start, utc(7, 0));
+        assert_eq!(entries[0].end, utc(8, 30));
+        assert_eq!(entries[0].tags, vec!["design", "frontend"]);
+    }
+
+    #[test]
+    fn parse_timewarrior_data() {
+        let entries = parse_entries(
+            ImportSource::Timewarrior,
+            include_str!("../tests/fixtures/timewarrior.data"),
+        )
+        .unwrap();
+
+        // The open interval is skipped.
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].project_name, "website");
+        assert_eq!(entries[0].start, utc(7, 0));
+        assert_eq!(entries[0].tags, vec!["design", "frontend"]);
+        assert_eq!(entries[0].note.as_deref(), Some("Landing page"));
+        assert_eq!(entries[1].tags, vec!["deep work"]);
+    }
+
+    #[test]
+    fn parse_timewarrior_export() {
+        let entries = parse_entries(
+            ImportSource::Timewarrior,
+            include_str!("../tests/fixtures/timewarrior-export.json"),
+        )
+        .unwrap();
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].end, utc(8, 30));
+        assert_eq!(entries[0].note.as_deref(), Some("Landing page"));
+    }
+
+    #[test]
+    fn parse_timeclock_file() {
+        let entries = parse_entries(
+            ImportSource::Timeclock,
+            include_str!("../tests/fixtures/hledger.timeclock"),
+        )
+        .unwrap();
+
+        // The last clock-in has no clock-out and is skipped.
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].project_name, "client:website");
+        assert_eq!(entries[0].start.hour(), 9);
+        assert_eq!(entries[0].to_duration().num_minutes(), 90);
+        assert_eq!(entries[0].note.as_deref(), Some("Landing page"));
+        assert_eq!(entries[0].tags, vec!["design", "frontend"]);
+        assert_eq!(entries[1].project_name, "internal");
+        assert_eq!(entries[1].to_duration().num_minutes(), 15);
+    }
+
+    #[test]
+    fn timeclock_round_trip() {
+        let entries = parse_entries(
+            ImportSource::Timeclock,
+            include_str!("../tests/fixtures/hledger.timeclock"),
+        )
+        .unwrap();
+        let mut log = TimeLog::new();
+        import_entries(&mut log, entries);
+
+        let mut out = Vec::new();
+        crate::export::export_timeclock(&log, NaiveDate::MIN..=NaiveDate::MAX, &mut out).unwrap();
+        let exported = String::from_utf8(out).unwrap();
+        assert_eq!(
+            exported,
+            "i 2026/09/07 09:00:00 client:website  Landing page  ; design:, frontend:\n\
+             o 2026/09/07 10:30:00\n\
+             i 2026/09/07 10:30:00 internal\n\
+             o 2026/09/07 10:45:00\n"
+        );
+
+        let reimported = parse_entries(ImportSource::Timeclock, &exported).unwrap();
+        assert!(import_entries(&mut log, reimported).imported.is_empty());
+    }
+
+    #[test]
+    fn parse_invalid_content() {
+        assert!(parse_entries(ImportSource::Timeclock, "o 2026/09/07 10:00").is_err());
+        assert!(parse_entries(ImportSource::Timeclock, "i 2026/09/07 account").is_err());
+        assert!(parse_entries(ImportSource::Watson, "{").is_err());
+        assert!(parse_entries(ImportSource::Timewarrior, "exc foo").is_err());
+        assert!(parse_entries(ImportSource::TogglCsv, "Foo,Bar\n1,2").is_err());
+    }
+
+    #[test]
+    fn reject_inverted_entries() {
+        let error = |source, content| parse_entries(source, content).err().unwrap().msg;
+        assert_eq!(
+            error(
+                ImportSource::Timeclock,
+                "i 2026/09/07 10:00 web\no 2026/09/07 09:00\n"
+            ),
+            "Invalid timeclock data: line 2: the entry does not end after it starts"
+        );
+        assert_eq!(
+            error(
+                ImportSource::Watson,
+                "[[1700003600, 1700003600, \"web\", \"id\"]]"
+            ),
+            "Invalid Watson data: frame 1: the entry does not end after it starts"
+        );
+        assert_eq!(
+            error(
+                ImportSource::Timewarrior,
+                "\ninc 20260907T090000Z - 20260907T080000Z # web\n"
+            ),
+            "Invalid Timewarrior data: line 2: the entry does not end after it starts"
+        );
+        let toggl = "Project,Description,Tags,Start date,Start time,End date,End time\n\
+                     web,,,2026-09-07,10:00:00,2026-09-07,09:00:00\n";
+        assert_eq!(
+            error(ImportSource::TogglCsv, toggl),
+            "Invalid Toggl data: row 2: the entry does not end after it starts"
+        );
+    }
+
+    #[test]
+    fn import_skips_duplicates() {
+        let content = include_str!("../tests/fixtures/watson-frames.json");
+        let mut log = TimeLog::new();
+
+        let first = import_entries(
+            &mut log,
+            parse_entries(ImportSource::Watson, content).unwrap(),
+        );
+        assert_eq!(first.imported.len(), 2);
+        assert_eq!(first.duplicates, 0);
+
+        let second = import_entries(
+            &mut log,
+            parse_entries(ImportSource::Watson, content).unwrap(),
+        );
+        assert!(second.imported.is_empty());
+        assert_eq!(second.duplicates, 2);
+    }
+}
@@ -0,0 +1,428 @@
+use crate::html::{escape_html, STYLE};
+use crate::report_creator::{escape_markdown, write_table, ReportCreator, TableRow};
+use crate::rounding::Rounding;
+use crate::TrackieError;
+use chrono::{Datelike, Duration, NaiveDate};
+use serde::{Deserialize, Serialize, Serializer};
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::io;
+use std::io::Write;
+use std::str::FromStr;
+
+/// The `[invoice]` table of the settings.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct InvoiceSettings {
+    /// The currency of the rates, e.g. `EUR`.
+    pub currency: Option<String>,
+    /// The tax rate in percent.
+    pub tax_rate: f64,
+    /// The billing increment. Falls back to the `round` setting.
+    pub round: Option<Rounding>,
+}
+
+/// The hourly rate of a project as configured in the `[rates]` table of the settings: either a
+/// single rate, or a list of rates that are effective from the given dates on.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum HourlyRate {
+    Fixed(f64),
+    Changes(Vec<RateChange>),
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RateChange {
+    /// The first day the rate applies to. Applies to all days before the next change if omitted.
+    pub from: Option<NaiveDate>,
+    pub rate: f64,
+}
+
+impl HourlyRate {
+    /// The rate that is effective on the given date, in cents.
+    pub fn on(&self, date: NaiveDate) -> Option<i64> {
+        let rate = match self {
+            HourlyRate::Fixed(rate) => Some(*rate),
+            HourlyRate::Changes(changes) => changes
+                .iter()
+                .filter(|c| c.from.is_none_or(|from| from <= date))
+                .max_by_key(|c| c.from)
+                .map(|c| c.rate),
+        };
+        rate.map(|r| (r * 100.0).round() as i64)
+    }
+}
+
+/// A calendar month, written like `2026-09`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Period {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+}
+
+impl FromStr for Period {
+    type Err = TrackieError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            TrackieError::new(format!("Invalid period '{}', expected e.g. 2026-09.", s).as_str())
+        };
+        let start =
+            NaiveDate::parse_from_str(&format!("{}-01", s), "%Y-%m-%d").map_err(|_| invalid())?;
+        let next_month = match start.month() {
+            12 => NaiveDate::from_ymd_opt(start.year() + 1, 1, 1),
+            m => NaiveDate::from_ymd_opt(start.year(), m + 1, 1),
+        };
+        Ok(Period {
+            start,
+            end: next_month.and_then(|d| d.pred_opt()).ok_or_else(invalid)?,
+        })
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Invoice {
+    pub project: String,
+    pub period: Period,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub currency: Option<String>,
+    pub items: Vec<LineItem>,
+    #[serde(serialize_with = "serialize_hours")]
+    pub hours: Duration,
+    #[serde(serialize_with = "serialize_money")]
+    pub subtotal: i64,
+    pub tax_rate: f64,
+    #[serde(serialize_with = "serialize_money")]
+    pub tax: i64,
+    #[serde(serialize_with = "serialize_money")]
+    pub total: i64,
+}
+
+/// The billed time of a single day. Amounts are given in cents.
+#[derive(Debug, Serialize)]
+pub struct LineItem {
+    pub date: NaiveDate,
+    pub description: String,
+    #[serde(serialize_with = "serialize_hours")]
+    pub hours: Duration,
+    #[serde(serialize_with = "serialize_money")]
+    pub rate: i64,
+    #[serde(serialize_with = "serialize_money")]
+    pub amount: i64,
+}
+
+impl Invoice {
+    /// Creates an invoice with a line item per day on which the project was tracked.
+    ///
+    /// The rounding of the [ReportCreator] is applied to each line item, so entry and day scopes
+    /// act as billing increments.
+    pub fn create(
+        report_creator: &ReportCreator,
+        project: &str,
+        period: Period,
+        rates: &BTreeMap<String, HourlyRate>,
+        settings: &InvoiceSettings,
+    ) -> Result<Invoice, TrackieError> {
+        let rate = rates.get(project).ok_or_else(|| {
+            TrackieError::new(
+                format!(
+                    "No hourly rate configured for project '{}'. Add it to the [rates] table of the settings or via `project add --rate`.",
+                    project
+                )
+                .as_str(),
+            )
+        })?;
+
+        let mut items = Vec::new();
+        let mut date = period.start;
+        while date <= period.end {
+            let day = report_creator.report_date(date);
+            if let Some(p) = day.find(project) {
+                let rate = rate.on(date).ok_or_else(|| {
+                    TrackieError::new(
+                        format!("No hourly rate for project '{}' on {}.", project, date).as_str(),
+                    )
+                })?;
+                let hours = p.rounded.unwrap_or(p.duration);
+                items.push(LineItem {
+                    date,
+                    description: match p.notes.is_empty() {
+                        true => p.project.clone(),
+                        false => p.notes.join("; "),
+                    },
+                    hours,
+                    rate,
+                    amount: (hours.num_seconds() as f64 * rate as f64 / 3600.0).round() as i64,
+                });
+            }
+            date = date.succ_opt().unwrap();
+        }
+
+        let subtotal = items.iter().map(|i| i.amount).sum();
+        let tax = (subtotal as f64 * settings.tax_rate / 100.0).round() as i64;
+        Ok(Invoice {
+            project: project.to_string(),
+            period,
+            currency: settings.currency.clone(),
+            hours: items.iter().fold(Duration::zero(), |sum, i| sum + i.hours),
+            items,
+            subtotal,
+            tax_rate: settings.tax_rate,
+            tax,
+            total: subtotal + tax,
+        })
+    }
+
+    fn title(&self) -> String {
+        format!(
+            "Invoice: {}, {} – {}",
+            self.project, self.period.start, self.period.end
+        )
+    }
+
+    fn money(&self, cents: i64) -> String {
+        let amount = format!("{:.2}", cents as f64 / 100.0);
+        match &self.currency {
+            Some(c) => format!("{} {}", amount, c),
+            None => amount,
+        }
+    }
+
+    /// The rows with the subtotal, tax and total: label, hours and amount.
+    fn totals(&self) -> Vec<(String, String, String)> {
+        vec![
+            (
+                "Subtotal".to_string(),
+                hours(&self.hours),
+                self.money(self.subtotal),
+            ),
+            (
+                format!("Tax ({}%)", self.tax_rate),
+                String::new(),
+                self.money(self.tax),
+            ),
+            ("Total".to_string(), String::new(), self.money(self.total)),
+        ]
+    }
+
+    pub fn write_markdown<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        writeln!(out, "# {}", escape_markdown(&self.title()))?;
+        writeln!(out)?;
+
+        let header = ["Date", "Description", "Hours", "Rate", "Amount"];
+        let mut rows = vec![
+            TableRow::Cells(header.iter().map(|h| h.to_string()).collect()),
+            TableRow::Alignment,
+        ];
+        for item in &self.items {
+            rows.push(TableRow::Cells(vec![
+                item.date.to_string(),
+                escape_markdown(&item.description),
+                hours(&item.hours),
+                self.money(item.rate),
+                self.money(item.amount),
+            ]));
+        }
+        for (label, hours, amount) in self.totals() {
+            rows.push(TableRow::Cells(vec![
+                String::new(),
+                format!("**{}**", label),
+                hours,
+                String::new(),
+                format!("**{}**", amount),
+            ]));
+        }
+        write_table(out, &rows, 2)
+    }
+
+    pub fn write_html<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        let title = escape_html(&self.title());
+        let mut html = String::new();
+        html.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+        let _ = writeln!(html, "<title>{}</title>", title);
+        let _ = writeln!(html, "<style>\n{}</style>\n</head>\n<body>", STYLE);
+        let _ = writeln!(html, "<h1>{}</h1>", title);
+        html.push_str(
+            "<table>\n<thead><tr><th>Date</th><th>Description</th><th class=\"duration\">Hours</th>\
+             <th class=\"duration\">Rate</th><th class=\"duration\">Amount</th></tr></thead>\n<tbody>\n",
+        );
+        for item in &self.items {
+            let _ = writeln!(
+                html,
+                "<tr><td>{}</td><td>{}</td><td class=\"duration\">{}</td>\
+                 <td class=\"duration\">{}</td><td class=\"duration\">{}</td></tr>",
+                item.date,
+                escape_html(&item.description),
+                hours(&item.hours),
+                self.money(item.rate),
+                self.money(item.amount)
+            );
+        }
+        html.push_str("</tbody>\n<tfoot>\n");
+        for (label, hours, amount) in self.totals() {
+            let _ = writeln!(
+                html,
+                "<tr><td></td><td>{}</td><td class=\"duration\">{}</td><td></td>\
+                 <td class=\"duration\">{}</td></tr>",
+                label, hours, amount
+            );
+        }
+        html.push_str("</tfoot>\n</table>\n</body>\n</html>\n");
+        out.write_all(html.as_bytes())
+    }
+}
+
+fn hours(d: &Duration) -> String {
+    format!("{:.2}", d.num_seconds() as f64 / 3600.0)
+}
+
+fn serialize_hours<S: Serializer>(d: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64((d.num_seconds() as f64 / 36.0).round() / 100.0)
+}
+
+fn serialize_money<S: Serializer>(cents: &i64, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64(*cents as f64 / 100.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time_log::{LogEntry, TimeLog};
+    use chrono::{Local, TimeZone};
+
+    fn entry(date: NaiveDate, minutes: i64, project: &str, note: Option<&str>) -> LogEntry {
+        let start = Local
+            .from_local_datetime(&date.and_hms_opt(9, 0, 0).unwrap())
+            .unwrap();
+        LogEntry {
+            project_name: project.to_string(),
+            start,
+            end: start + Duration::minutes(minutes),
+            note: note.map(String::from),
+            tags: Vec::new(),
+        }
+    }
+
+    fn log() -> TimeLog {
+        let date = |m, d| NaiveDate::from_ymd_opt(2026, m, d).unwrap();
+        TimeLog::new_testing_only(BTreeMap::from([
+            (
+                date(9, 7),
+                vec![
+                    entry(date(9, 7), 80, "web", Some("Landing page")),
+                    entry(date(9, 7), 60, "other", None),
+                ],
+            ),
+            (date(9, 15), vec![entry(date(9, 15), 30, "web", None)]),
+            (date(10, 1), vec![entry(date(10, 1), 60, "web", None)]),
+        ]))
+    }
+
+    fn rates() -> BTreeMap<String, HourlyRate> {
+        let settings: toml::Value = toml::from_str(
+            "web = [{ rate = 80 }, { from = \"2026-09-15\", rate = 100.5 }]\nother = 50",
+        )
+        .unwrap();
+        settings.try_into().unwrap()
+    }
+
+    fn settings() -> InvoiceSettings {
+        InvoiceSettings {
+            currency: Some("EUR".to_string()),
+            tax_rate: 19.0,
+            round: None,
+        }
+    }
+
+    #[test]
+    fn parse_period() {
+        let p: Period = "2026-12".parse().unwrap();
+        assert_eq!(p.start, NaiveDate::from_ymd_opt(2026, 12, 1).unwrap());
+        assert_eq!(p.end, NaiveDate::from_ymd_opt(2026, 12, 31).unwrap());
+        assert_eq!("2028-02".parse::<Period>().unwrap().end.day(), 29);
+        assert!("2026-13".parse::<Period>().is_err());
+        assert!("2026".parse::<Period>().is_err());
+    }
+
+    #[test]
+    fn rate_changes() {
+        let rates = rates();
+        let date = |d| NaiveDate::from_ymd_opt(2026, 9, d).unwrap();
+
+        assert_eq!(rates["web"].on(date(14)), Some(8000));
+        assert_eq!(rates["web"].on(date(15)), Some(10050));
+        assert_eq!(rates["other"].on(date(1)), Some(5000));
+
+        let later = HourlyRate::Changes(vec![RateChange {
+            from: Some(date(10)),
+            rate: 1.0,
+        }]);
+        assert_eq!(later.on(date(9)), None);
+    }
+
+    #[test]
+    fn create_invoice() {
+        let log = log();
+        let rc = ReportCreator::new(&log).with_rounding(Some("15m:up".parse().unwrap()));
+        let invoice = Invoice::create(
+            &rc,
+            "web",
+            "2026-09".parse().unwrap(),
+            &rates(),
+            &settings(),
+        )
+        .unwrap();
+
+        assert_eq!(invoice.items.len(), 2);
+        assert_eq!(invoice.items[0].description, "Landing page");
+        assert_eq!(invoice.items[0].hours, Duration::minutes(90));
+        assert_eq!(invoice.items[0].amount, 12000);
+        assert_eq!(invoice.items[1].description, "web");
+        assert_eq!(invoice.items[1].amount, 5025);
+        assert_eq!(invoice.subtotal, 17025);
+        assert_eq!(invoice.tax, 3235);
+        assert_eq!(invoice.total, 20260);
+
+        let json = serde_json::to_value(&invoice).unwrap();
+        assert_eq!(json["items"][0]["hours"], 1.5);
+        assert_eq!(json["items"][1]["rate"], 100.5);
+        assert_eq!(json["total"], 202.6);
+    }
+
+    #[test]
+    fn missing_rate() {
+        let log = log();
+        let rc = ReportCreator::new(&log);
+        let period = "2026-09".parse().unwrap();
+
+        assert!(Invoice::create(&rc, "unknown", period, &rates(), &settings()).is_err());
+    }
+
+    #[test]
+    fn render_invoice() {
+        let log = log();
+        let rc = ReportCreator::new(&log);
+        let invoice = Invoice::create(
+            &rc,
+            "web",
+            "2026-09".parse().unwrap(),
+            &rates(),
+            &settings(),
+        )
+        .unwrap();
+
+        let mut md = Vec::new();
+        invoice.write_markdown(&mut md).unwrap();
+        assert_eq!(
+            String::from_utf8(md).unwrap(),
+            include_str!("../tests/fixtures/invoice.md")
+        );
+
+        let mut html = Vec::new();
+        invoice.write_html(&mut html).unwrap();
+        let html = String::from_utf8(html).unwrap();
+        assert!(html.contains("<td>Landing page</td>"));
+        assert!(html.contains("<td>Tax (19%)</td>"));
+    }
+}
@@ -1,129 +1,981 @@
 use std::error::Error;
 
-use chrono::Local;
+use chrono::{Datelike, Duration, Local, NaiveDate, TimeZone};
 
 use crate::cli::{
-    CompletionCommand, Opts, Subcommand, TimingCommand, DEFAULT_EMPTY_STATUS_MSG,
-    DEFAULT_STATUS_FORMAT,
+    CalendarAction, CompletionCommand, ExportFormat, HooksAction, IdleOptions, InvoiceFormat, Opts,
+    ProjectAction, ReportFormat, Subcommand, TimingCommand, DEFAULT_EMPTY_STATUS_MSG,
+    DEFAULT_STATUS_FORMAT, EXIT_NOT_TRACKING,
+};
+use crate::config::Config;
+use crate::csv::CsvWriter;
+use crate::detect::detect_project;
+use crate::events::{run_hooks, TrackingEvent};
+use crate::export::{export_csv, export_ics, export_timeclock};
+use crate::git::{commits_since, expand_home, git, RepositoryLog};
+use crate::git_hooks::{install_hooks, run_hook, uninstall_hooks};
+use crate::html::write_html;
+use crate::idle::{ask_idle_policy, end_at, is_idle, IdleOutcome, IdlePolicy};
+use crate::import::{import_entries, parse_entries};
+use crate::invoice::{HourlyRate, Invoice};
+use crate::persistence::{load_config, load_or_create_log, save_log, FileHandler};
+use crate::picker::pick_project;
+use crate::pretty_string::DurationFormat;
+use crate::project::{
+    closest_match, is_valid_color, is_within, Budget, BudgetStatus, UnknownProjects,
 };
-use crate::persistence::{load_or_create_log, save_log, FileHandler};
-use crate::pretty_string::PrettyString;
 use crate::report_creator::ReportCreator;
-use crate::time_log::TimeLog;
+use crate::rounding::RoundingScope;
+use crate::status_format::{StatusContext, StatusFormat, StatusJson};
+use crate::template::render_template;
+use crate::time_log::{PendingLog, TimeLog};
+use crate::tui::run_tui;
 use clap::{Command, CommandFactory};
-use clap_complete::{generate, Generator};
+use clap_complete::env::{Bash, Elvish, EnvCompleter, Fish, Powershell, Zsh};
+use clap_complete::{generate, Shell};
 use colored::Colorize;
+use std::env;
 use std::fmt::Display;
 use std::fmt::Formatter;
+use std::fs::File;
 use std::io;
+use std::io::{BufWriter, IsTerminal, Write};
+use std::path::PathBuf;
 
+mod calendar;
 pub mod cli;
+mod config;
+mod csv;
+mod detect;
+mod duration;
+mod events;
+mod export;
+mod git;
+mod git_hooks;
+mod html;
+mod idle;
+mod import;
+mod invoice;
 pub mod persistence;
+mod picker;
 mod pretty_string;
+mod project;
 mod report_creator;
+mod rounding;
+mod status_format;
+mod template;
 mod time_log;
+mod tui;
 
 pub fn run_app(o: Opts, fh: &mut dyn FileHandler) -> Result<(), TrackieError> {
     let mut modified = false;
     let mut log = load_or_create_log(fh)?;
-    let report_creator = ReportCreator::new(&log);
+    let config = load_config(fh)?;
+    let mut tracked_before = log.pending.clone();
 
     match o.sub_cmd {
         Subcommand::Start(p) => {
             modified = true;
-            start_tracking(&mut log, p)?;
+            start_tracking(&mut log, p, &config)?;
         }
-        Subcommand::Stop(_) => {
+        Subcommand::Switch(mut p) => {
             modified = true;
-            let pending = log.stop_pending()?;
-            let dur = pending.get_pending_duration();
-            println!(
-                "Tracked {} on project {}",
-                dur.to_pretty_string().bold(),
-                pending.project_name.italic()
-            );
+            if p.project_name.is_none() {
+                p.project_name = Some(pick(&log)?);
+            }
+            start_tracking(&mut log, p, &config)?;
+        }
+        Subcommand::Stop(s) => {
+            modified = true;
+            let stopped =
+                match handle_idle(&mut log, &mut tracked_before, &s.idle, &config, true, true)? {
+                    IdleOutcome::Trimmed(entry) => {
+                        Some((entry.project_name.clone(), entry.to_duration()))
+                    }
+                    IdleOutcome::Discarded => None,
+                    IdleOutcome::Unchanged => {
+                        let pending = log.stop_pending()?;
+                        Some((pending.project_name.clone(), pending.get_pending_duration()))
+                    }
+                };
+            if let Some((project, dur)) = stopped {
+                let duration_format = s.duration_format.or(config.duration_format);
+                println!(
+                    "Tracked {} on project {}",
+                    duration_format.unwrap_or_default().format(&dur).bold(),
+                    project.italic()
+                );
+                let today = Local::now().date_naive();
+                if let Some(status) = log.budget_status(&project, today) {
+                    warn_budget(
+                        &project,
+                        &status,
+                        status.consumed - dur,
+                        &config,
+                        duration_format.unwrap_or_default(),
+                    );
+                }
+            }
         }
         Subcommand::Report(o) => {
+            let repositories = match o.with_commits {
+                true => Some(repository_logs(&log, &config, o.days)?),
+                false => None,
+            };
+            let report_creator = ReportCreator::new(&log)
+                .with_rounding(o.round.or(config.round))
+                .with_targets(config.targets())
+                .with_depth(o.depth)
+                .with_project(o.project)
+                .with_commits(repositories)
+                .with_duration_format(
+                    o.duration_format
+                        .or(config.duration_format)
+                        .unwrap_or_default(),
+                );
             let report = report_creator.report_days(Local::today(), o.days, o.include_empty_days);
-            match o.json {
-                true => println!("{}", serde_json::to_string_pretty(&report)?),
-                false => println!("{}", report),
+            let mut out = output(&o.output)?;
+            match (o.json, o.format) {
+                (true, _) | (_, ReportFormat::Json) if o.summary => writeln!(
+                    out,
+                    "{}",
+                    serde_json::to_string_pretty(&report_creator.report_summary(&report))?
+                )?,
+                (_, ReportFormat::Text) if o.summary => {
+                    writeln!(out, "{}", report_creator.report_summary(&report))?
+                }
+                _ if o.summary => {
+                    return Err(TrackieError::new(
+                        "The summary is only available as text or JSON.",
+                    ))
+                }
+                _ if o.template.is_some() => {
+                    let template = std::fs::read_to_string(o.template.as_ref().unwrap())?;
+                    write!(out, "{}", render_template(&report, &template)?)?
+                }
+                (true, _) | (_, ReportFormat::Json) => {
+                    writeln!(out, "{}", serde_json::to_string_pretty(&report)?)?
+                }
+                (_, ReportFormat::Csv) => report.write_csv(
+                    &mut CsvWriter::new(&mut out, o.csv.delimiter),
+                    !o.csv.no_header,
+                )?,
+                (_, ReportFormat::Org) => report.write_org(&mut out)?,
+                (_, ReportFormat::Markdown) => report.write_markdown(&mut out)?,
+                (_, ReportFormat::Html) => write_html(&report, &log.projects, &mut out)?,
+                (_, ReportFormat::Text) => writeln!(out, "{}", report)?,
             };
+            out.flush()?;
         }
-        Subcommand::Status(s) => match &log.pending {
-            None => {
-                let msg = s
-                    .fallback
-                    .unwrap_or_else(|| DEFAULT_EMPTY_STATUS_MSG.to_string());
-
-                return Err(TrackieError {
-                    msg,
-                    print_as_error: false,
-                });
+        Subcommand::Status(s) => {
+            // Saved right away, as the status exits with an error if nothing is tracked anymore.
+            // The warning is left out when the status is embedded, e.g. in a shell prompt.
+            let warn = io::stdout().is_terminal();
+            if !matches!(
+                handle_idle(&mut log, &mut tracked_before, &s.idle, &config, false, warn)?,
+                IdleOutcome::Unchanged
+            ) {
+                save_changes(fh, &log, &mut tracked_before, &config)?;
             }
-            Some(p) => {
-                let format = s
-                    .format
-                    .unwrap_or_else(|| DEFAULT_STATUS_FORMAT.to_string());
+            match &log.pending {
+                None if s.json => {
+                    return Err(TrackieError {
+                        msg: serde_json::to_string_pretty(&StatusJson::idle())?,
+                        print_as_error: false,
+                        exit_code: EXIT_NOT_TRACKING,
+                    });
+                }
+                None => {
+                    let msg = s
+                        .fallback
+                        .unwrap_or_else(|| DEFAULT_EMPTY_STATUS_MSG.to_string());
 
-                let output = format
-                    .replace("%p", p.project_name.as_str())
-                    .replace("%d", p.start.format("%F").to_string().as_str())
-                    .replace("%t", p.start.format("%R").to_string().as_str())
-                    .replace("%D", p.get_pending_duration().to_pretty_string().as_str());
+                    return Err(TrackieError {
+                        msg,
+                        print_as_error: false,
+                        exit_code: 1,
+                    });
+                }
+                Some(p) => {
+                    if s.check_project {
+                        warn_other_project(&log, &p.project_name)?;
+                    }
+                    let today = Local::now().date_naive();
+                    let week_start =
+                        today - Duration::days(today.weekday().num_days_from_monday() as i64);
+                    let ctx = StatusContext {
+                        pending: p,
+                        duration: p.get_pending_duration(),
+                        today: log.tracked_time(today..=today),
+                        week: log.tracked_time(week_start..=today),
+                        daily_target: config.targets().map(|t| t.for_date(today, &log.calendar)),
+                        budget_remaining: log
+                            .budget_status(&p.project_name, today)
+                            .map(|b| b.remaining),
+                        duration_format: s
+                            .duration_format
+                            .or(config.duration_format)
+                            .unwrap_or_default(),
+                    };
 
-                println!("{}", output);
+                    if s.json {
+                        println!("{}", serde_json::to_string_pretty(&StatusJson::from(&ctx))?);
+                    } else {
+                        let format = s
+                            .format
+                            .unwrap_or_else(|| DEFAULT_STATUS_FORMAT.to_string());
+                        println!("{}", StatusFormat::parse(&format)?.render(&ctx));
+                    }
+                }
             }
-        },
-        Subcommand::Resume(_) => match (&log.pending, log.get_latest_entry()) {
-            (None, Some(s)) => {
-                modified = true;
-                let name = s.project_name.clone();
-                start_tracking(&mut log, TimingCommand { project_name: name })?;
+        }
+        Subcommand::Resume(r) => {
+            let outcome =
+                handle_idle(&mut log, &mut tracked_before, &r.idle, &config, false, true)?;
+            if !matches!(outcome, IdleOutcome::Unchanged) {
+                save_changes(fh, &log, &mut tracked_before, &config)?;
             }
-            (Some(p), _) => {
-                return Err(TrackieError::new(
-                    format!("Already tracking time for project {}", p.project_name).as_str(),
-                ))
+            match (&log.pending, log.get_latest_entry()) {
+                // After a split, the work is already tracked again.
+                (Some(p), _) if matches!(outcome, IdleOutcome::Trimmed(_)) => println!(
+                    "Tracking time for project {}",
+                    p.project_name.as_str().italic()
+                ),
+                (None, Some(s)) => {
+                    modified = true;
+                    let cmd = TimingCommand {
+                        project_name: Some(s.project_name.clone()),
+                        note: None,
+                        tags: s.tags.clone(),
+                    };
+                    start_tracking(&mut log, cmd, &config)?;
+                }
+                (Some(p), _) => {
+                    return Err(TrackieError::new(
+                        format!("Already tracking time for project {}", p.project_name).as_str(),
+                    ))
+                }
+                (_, None) => {
+                    return Err(TrackieError::new(
+                        "Unable to find latest time log. Maybe no time was ever tracked?",
+                    ));
+                }
             }
-            (_, None) => {
+        }
+        Subcommand::Tui(_) => run_tui(fh, &config)?,
+        Subcommand::Balance(b) => {
+            let targets = config.targets().ok_or_else(|| {
+                TrackieError::new(
+                    "No targets configured. Set 'daily_target' or '[targets]' in the settings.",
+                )
+            })?;
+            let today = Local::now().date_naive();
+            let from = b
+                .from
+                .or(config.balance_start)
+                .or_else(|| log.first_date())
+                .unwrap_or(today);
+            let to = b.to.unwrap_or(today);
+            let report = ReportCreator::new(&log)
+                .with_duration_format(
+                    b.duration_format
+                        .or(config.duration_format)
+                        .unwrap_or_default(),
+                )
+                .report_balance(from..=to, &targets);
+            match b.json {
+                true => println!("{}", serde_json::to_string_pretty(&report)?),
+                false => print!("{}", report),
+            };
+        }
+        Subcommand::Invoice(i) => {
+            let rounding = i.round.or(config.invoice.round).or(config.round);
+            // Every line item is billed on its own, so there is no total to round.
+            if rounding.is_some_and(|r| r.scope == RoundingScope::Report) {
                 return Err(TrackieError::new(
-                    "Unable to find latest time log. Maybe no time was ever tracked?",
+                    "Invoices cannot be rounded per report. Use the entry or day scope instead.",
                 ));
             }
+            let report_creator = ReportCreator::new(&log).with_rounding(rounding);
+            let project = log.projects.resolve(&i.project).to_string();
+            let mut rates = config.rates.clone();
+            if let Some(rate) = log.projects.get(&project).and_then(|p| p.rate) {
+                rates
+                    .entry(project.clone())
+                    .or_insert(HourlyRate::Fixed(rate));
+            }
+            let invoice =
+                Invoice::create(&report_creator, &project, i.period, &rates, &config.invoice)?;
+            let mut out = output(&i.output)?;
+            match i.format {
+                InvoiceFormat::Json => {
+                    writeln!(out, "{}", serde_json::to_string_pretty(&invoice)?)?
+                }
+                InvoiceFormat::Markdown => invoice.write_markdown(&mut out)?,
+                InvoiceFormat::Html => invoice.write_html(&mut out)?,
+            }
+            out.flush()?;
+        }
+        Subcommand::Project(p) => modified = manage_project(&mut log, p.action, &config)?,
+        Subcommand::Calendar(c) => match c.action {
+            CalendarAction::Add { date, kind } => {
+                modified = true;
+                log.calendar.mark(date, kind);
+                println!("Marked {} as {}", date, kind);
+            }
+            CalendarAction::Remove { date } => {
+                modified = true;
+                if log.calendar.unmark(date).is_none() {
+                    return Err(TrackieError::new(
+                        format!("{} is not marked in the calendar.", date).as_str(),
+                    ));
+                }
+                println!("Removed {} from the calendar", date);
+            }
+            CalendarAction::List => {
+                for (date, kind) in log.calendar.days_off() {
+                    println!("{} {}", date.format("%a. %F"), kind);
+                }
+            }
+        },
+        Subcommand::Export(e) => {
+            if let (Some(from), Some(to)) = (e.from, e.to) {
+                if from > to {
+                    return Err(TrackieError::new(
+                        format!("The export cannot start ({}) after it ends ({}).", from, to)
+                            .as_str(),
+                    ));
+                }
+            }
+            let days = e.from.unwrap_or(NaiveDate::MIN)..=e.to.unwrap_or(NaiveDate::MAX);
+            match e.format {
+                ExportFormat::Csv => export_csv(&log, days, &e.csv, io::stdout().lock())?,
+                ExportFormat::Ics => export_ics(&log, days, io::stdout().lock())?,
+                ExportFormat::Timeclock => export_timeclock(&log, days, io::stdout().lock())?,
+            }
+        }
+        Subcommand::Import(i) => {
+            let content = std::fs::read_to_string(&i.file)?;
+            let summary = import_entries(&mut log, parse_entries(i.source, &content)?);
+            if i.dry_run {
+                for entry in &summary.imported {
+                    println!(
+                        "{} - {} {}",
+                        entry.start.format("%F %R"),
+                        entry.end.format("%F %R"),
+                        entry.project_name
+                    );
+                }
+            } else {
+                modified = true;
+            }
+            println!(
+                "{} {} entries, skipped {} duplicates",
+                if i.dry_run {
+                    "Would import"
+                } else {
+                    "Imported"
+                },
+                summary.imported.len(),
+                summary.duplicates
+            );
+        }
+        Subcommand::Hooks(h) => match h.action {
+            HooksAction::Install => {
+                let installed = install_hooks(&env::current_dir()?, &env::current_exe()?)?;
+                if installed.is_empty() {
+                    println!("The hooks are already installed.");
+                }
+                for path in installed {
+                    println!("Installed {}", path.display());
+                }
+            }
+            HooksAction::Uninstall => {
+                let removed = uninstall_hooks(&env::current_dir()?)?;
+                if removed.is_empty() {
+                    println!("No hooks are installed.");
+                }
+                for path in removed {
+                    println!("Removed {}", path.display());
+                }
+            }
+            // Git must never fail because of trackie, so errors are only reported.
+            HooksAction::Run { hook, args } => {
+                match run_hook(&mut log, hook, &args, &env::current_dir()?, &config) {
+                    // Git waits for its hooks, so the event hooks are not run from them.
+                    Ok(true) => save_log(fh, &log)?,
+                    Ok(false) => {}
+                    Err(e) => eprintln!("{} {}", "WARN:".yellow(), e),
+                }
+            }
         },
         Subcommand::Completion(CompletionCommand { shell }) => {
             let mut cmd = Opts::command();
-            print_completions(shell, &mut cmd);
+            print_completions(shell, &mut cmd)?;
         }
     }
 
     if modified {
-        save_log(fh, &log)?;
+        save_changes(fh, &log, &mut tracked_before, &config)?;
     }
 
     Ok(())
 }
 
-fn start_tracking(log: &mut TimeLog, p: TimingCommand) -> Result<(), Box<dyn Error>> {
-    if let Some(warn) = log.start_log(&p.project_name)? {
-        println!("{} {}", "WARN:".yellow(), warn);
+/// Saves the log and runs the hooks of the event that replaced the work `tracked_before`.
+fn save_changes(
+    fh: &mut dyn FileHandler,
+    log: &TimeLog,
+    tracked_before: &mut Option<PendingLog>,
+    config: &Config,
+) -> Result<(), TrackieError> {
+    save_log(fh, log)?;
+    if let Some(event) = TrackingEvent::between(tracked_before.as_ref(), log) {
+        for warning in run_hooks(&config.hooks, &event) {
+            eprintln!("{} {}", "WARN:".yellow(), warning);
+        }
+    }
+    *tracked_before = log.pending.clone();
+    Ok(())
+}
+
+/// Handles tracked work that has been running for longer than the idle threshold, as chosen via
+/// `options` or, in a terminal, by the user. When `stopping`, a split is the same as a trim.
+/// Otherwise, the idle work is kept and only mentioned if `warn`. Discarded work is removed from
+/// `tracked_before` as well, so that it is not reported to the hooks as stopped.
+fn handle_idle(
+    log: &mut TimeLog,
+    tracked_before: &mut Option<PendingLog>,
+    options: &IdleOptions,
+    config: &Config,
+    stopping: bool,
+    warn: bool,
+) -> Result<IdleOutcome, TrackieError> {
+    let threshold = config.idle_threshold();
+    let Some(pending) = log.pending.clone().filter(|p| is_idle(p, threshold)) else {
+        return Ok(IdleOutcome::Unchanged);
+    };
+    let format = config.duration_format.unwrap_or_default();
+    let default_end = match options.idle_end {
+        Some(time) => end_at(time, pending.start, Local::now())?,
+        None => pending.start + threshold,
+    };
+    let (policy, end) = match options.idle_policy {
+        Some(policy) => (policy, default_end),
+        None if io::stdin().is_terminal() && io::stdout().is_terminal() => {
+            ask_idle_policy(&pending, default_end, format)?
+        }
+        None => {
+            if warn {
+                eprintln!(
+                    "{} Project {} has been tracked for {}. Pass --idle-policy to trim, split or discard it.",
+                    "WARN:".yellow(),
+                    pending.project_name,
+                    format.format(&pending.get_pending_duration())
+                );
+            }
+            return Ok(IdleOutcome::Unchanged);
+        }
+    };
+    match policy {
+        IdlePolicy::Keep => Ok(IdleOutcome::Unchanged),
+        IdlePolicy::Trim | IdlePolicy::Split => {
+            let resume = policy == IdlePolicy::Split && !stopping;
+            let entry = log.trim_pending(end, resume)?;
+            eprintln!(
+                "Ended project {} at {}{}",
+                entry.project_name,
+                end.format("%H:%M"),
+                if resume {
+                    ", tracking it again from now on"
+                } else {
+                    ""
+                }
+            );
+            Ok(IdleOutcome::Trimmed(entry))
+        }
+        IdlePolicy::Discard => {
+            log.pending = None;
+            *tracked_before = None;
+            eprintln!(
+                "Discarded {} tracked on project {}",
+                format.format(&pending.get_pending_duration()),
+                pending.project_name
+            );
+            Ok(IdleOutcome::Discarded)
+        }
+    }
+}
+
+fn start_tracking(
+    log: &mut TimeLog,
+    mut p: TimingCommand,
+    config: &Config,
+) -> Result<(), Box<dyn Error>> {
+    let project_name = match p.project_name {
+        Some(name) => name,
+        None => match detect_project(&env::current_dir()?)? {
+            Some(detected) => {
+                for tag in detected.tags {
+                    if !p.tags.contains(&tag) {
+                        p.tags.push(tag);
+                    }
+                }
+                detected.name
+            }
+            None => pick(log)?,
+        },
+    };
+    let (project_name, warnings) = start_project(log, &project_name, p.note, p.tags, config)?;
+    for warning in warnings {
+        println!("{} {}", "WARN:".yellow(), warning);
     }
     println!(
         "Tracking time for project {}",
-        p.project_name.as_str().italic()
+        project_name.as_str().italic()
     );
     Ok(())
 }
 
-fn print_completions<G: Generator>(gen: G, cmd: &mut Command) {
-    generate(gen, cmd, cmd.get_name().to_string(), &mut io::stdout());
+/// Starts tracking `name` or the project it is an alias of, which is refused for unknown projects
+/// if configured so. Returns the tracked project and the warnings to show, e.g. that the project
+/// is archived or reached a budget threshold. Used by the CLI and the terminal UI alike.
+pub fn start_project(
+    log: &mut TimeLog,
+    name: &str,
+    note: Option<String>,
+    tags: Vec<String>,
+    config: &Config,
+) -> Result<(String, Vec<String>), Box<dyn Error>> {
+    let project_name = log.projects.resolve(name).to_string();
+    let mut warnings = Vec::new();
+    if !log.is_known_project(&project_name) {
+        let suggestion = closest_match(&project_name, log.project_names().into_iter())
+            .map(|s| format!(" Did you mean {}?", s))
+            .unwrap_or_default();
+        match config.unknown_projects {
+            UnknownProjects::Allow => {}
+            UnknownProjects::Warn => {
+                warnings.push(format!("{} is a new project.{}", project_name, suggestion))
+            }
+            UnknownProjects::Refuse => {
+                return Err(TrackieError::new(
+                    format!(
+                        "Unknown project {}.{} Register it via `trackie project add {}`.",
+                        project_name, suggestion, project_name
+                    )
+                    .as_str(),
+                )
+                .into())
+            }
+        }
+    }
+    if log
+        .projects
+        .get(&project_name)
+        .is_some_and(|project| project.archived)
+    {
+        warnings.push(format!("Project {} is archived.", project_name));
+    }
+    if let Some(warn) = log.start_annotated_log(&project_name, note, tags)? {
+        warnings.push(warn);
+    }
+    if let Some(status) = log.budget_status(&project_name, Local::now().date_naive()) {
+        warnings.extend(budget_warning(
+            &project_name,
+            &status,
+            Duration::zero(),
+            config,
+            config.duration_format.unwrap_or_default(),
+        ));
+    }
+    Ok((project_name, warnings))
+}
+
+/// Reads the commits of all linked and configured repositories since the first reported day.
+/// Repositories that cannot be read are skipped with a warning.
+fn repository_logs(
+    log: &TimeLog,
+    config: &Config,
+    days: u32,
+) -> Result<Vec<RepositoryLog>, TrackieError> {
+    let linked = log
+        .projects
+        .iter()
+        .filter_map(|(name, p)| Some((Some(name.clone()), p.repository.clone()?)));
+    let configured = config
+        .repositories
+        .iter()
+        .map(|path| (None, expand_home(path)));
+    let repositories: Vec<(Option<String>, PathBuf)> = linked.chain(configured).collect();
+    if repositories.is_empty() {
+        return Err(TrackieError::new(
+            "No repositories to read commits from. Link one via `trackie project add <project> --repo <path>` or set `repositories` in the settings.",
+        ));
+    }
+
+    let first_day = Local::now().date_naive() - Duration::days(days as i64 - 1);
+    let since = Local
+        .from_local_datetime(&first_day.and_hms_opt(0, 0, 0).unwrap())
+        .earliest()
+        .unwrap_or_else(Local::now);
+    let mut logs = Vec::new();
+    for (project, path) in repositories {
+        match commits_since(&path, since) {
+            Ok(commits) => logs.push(RepositoryLog { project, commits }),
+            Err(e) => eprintln!("{} {}", "WARN:".yellow(), e),
+        }
+    }
+    Ok(logs)
+}
+
+/// Lets the user pick one of the recently and frequently tracked projects.
+fn pick(log: &TimeLog) -> Result<String, TrackieError> {
+    pick_project(&log.projects_by_frecency(Local::now()))?
+        .ok_or_else(|| TrackieError::new("No project selected."))
+}
+
+/// Warns if the working directory belongs to another project than the tracked one.
+fn warn_other_project(log: &TimeLog, tracked: &str) -> Result<(), TrackieError> {
+    if let Some(detected) = detect_project(&env::current_dir()?)? {
+        let project = log.projects.resolve(&detected.name);
+        if !is_within(tracked, project) {
+            eprintln!(
+                "{} This directory belongs to project {}, but {} is tracked.",
+                "WARN:".yellow(),
+                project,
+                tracked
+            );
+        }
+    }
+    Ok(())
+}
+
+fn manage_project(
+    log: &mut TimeLog,
+    action: ProjectAction,
+    config: &Config,
+) -> Result<bool, TrackieError> {
+    let format = config.duration_format.unwrap_or_default();
+    match action {
+        ProjectAction::Add {
+            name,
+            description,
+            color,
+            client,
+            rate,
+            repo,
+        } => {
+            if let Some(project) = log.projects.alias_of(&name) {
+                return Err(TrackieError::new(
+                    format!("{} is an alias of project {}.", name, project).as_str(),
+                ));
+            }
+            if let Some(color) = color.as_deref().filter(|c| !is_valid_color(c)) {
+                return Err(TrackieError::new(
+                    format!(
+                        "Invalid colour '{}'. Use a hex colour like #4e79a7 or a CSS colour name.",
+                        color
+                    )
+                    .as_str(),
+                ));
+            }
+            let project = log.projects.get_or_insert(&name);
+            project.description = description.or(project.description.take());
+            project.color = color.or(project.color.take());
+            project.client = client.or(project.client.take());
+            project.rate = rate.or(project.rate);
+            if let Some(repo) = repo {
+                git(&repo, &["rev-parse", "--git-dir"])?;
+                project.repository = Some(repo.canonicalize()?);
+            }
+            println!("Registered project {}", name.italic());
+            Ok(true)
+        }
+        ProjectAction::List { all } => {
+            for name in log.project_names() {
+                let project = log.projects.get(name);
+                if project.is_some_and(|p| p.archived) && !all {
+                    continue;
+                }
+                let details = project
+                    .map(|p| {
+                        [
+                            p.client.as_ref().map(|c| format!("[{}]", c)),
+                            p.description.clone(),
+                            p.archived.then(|| "(archived)".to_string()),
+                        ]
+                        .into_iter()
+                        .flatten()
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                    })
+                    .unwrap_or_default();
+                println!("{:<25} {}", name.bold(), details);
+            }
+            Ok(false)
+        }
+        ProjectAction::Rename {
+            name,
+            new_name,
+            dry_run: true,
+        } => {
+            let name = log.projects.resolve(&name).to_string();
+            log.check_rename(&name, &new_name)?;
+            println!(
+                "Would rename {} entries of project {} to {}",
+                log.count_entries_within(&name),
+                name.italic(),
+                new_name.italic()
+            );
+            Ok(false)
+        }
+        ProjectAction::Rename { name, new_name, .. } => {
+            let name = log.projects.resolve(&name).to_string();
+            let renamed = log.rename_project(&name, &new_name)?;
+            println!(
+                "Renamed project {} to {} ({} entries)",
+                name.italic(),
+                new_name.italic(),
+                renamed
+            );
+            Ok(true)
+        }
+        ProjectAction::Merge {
+            mut projects,
+            dry_run,
+        } => {
+            let target = projects.pop().unwrap();
+            let target = log.projects.resolve(&target).to_string();
+            let sources: Vec<String> = projects
+                .iter()
+                .map(|p| log.projects.resolve(p).to_string())
+                .collect();
+            let sources: Vec<&str> = sources.iter().map(String::as_str).collect();
+            if dry_run {
+                log.check_merge(&sources, &target)?;
+                let count: usize = sources.iter().map(|s| log.count_entries_within(s)).sum();
+                println!(
+                    "Would merge {} entries into project {}",
+                    count,
+                    target.italic()
+                );
+                return Ok(false);
+            }
+            let merged = log.merge_projects(&sources, &target)?;
+            println!("Merged {} entries into project {}", merged, target.italic());
+            Ok(true)
+        }
+        ProjectAction::Archive { name, undo } => {
+            let name = known_project(log, &name)?;
+            log.projects.get_or_insert(&name).archived = !undo;
+            match undo {
+                true => println!("Restored project {}", name.italic()),
+                false => println!("Archived project {}", name.italic()),
+            }
+            Ok(true)
+        }
+        ProjectAction::Alias {
+            name,
+            alias,
+            remove: true,
+        } => {
+            let name = known_project(log, &name)?;
+            let aliases = &mut log.projects.get_or_insert(&name).aliases;
+            match aliases.iter().position(|a| *a == alias) {
+                Some(idx) => aliases.remove(idx),
+                None => {
+                    return Err(TrackieError::new(
+                        format!("{} is not an alias of project {}.", alias, name).as_str(),
+                    ))
+                }
+            };
+            println!("Removed alias {} of project {}", alias, name.italic());
+            Ok(true)
+        }
+        ProjectAction::Alias { name, alias, .. } => {
+            let name = known_project(log, &name)?;
+            if log.is_known_project(&alias) {
+                return Err(TrackieError::new(
+                    format!("{} is already the name or alias of a project.", alias).as_str(),
+                ));
+            }
+            log.projects
+                .get_or_insert(&name)
+                .aliases
+                .push(alias.clone());
+            println!("Added alias {} for project {}", alias, name.italic());
+            Ok(true)
+        }
+        ProjectAction::Info { name } => {
+            let name = known_project(log, &name)?;
+            println!("{:<13}{}", "Project:", name.bold());
+            if let Some(p) = log.projects.get(&name) {
+                let currency = config.invoice.currency.as_deref().unwrap_or_default();
+                let fields = [
+                    ("Description:", p.description.clone()),
+                    ("Client:", p.client.clone()),
+                    ("Color:", p.color.clone()),
+                    ("Rate:", p.rate.map(|r| format!("{:.2} {}", r, currency))),
+                    (
+                        "Aliases:",
+                        Some(p.aliases.join(", ")).filter(|a| !a.is_empty()),
+                    ),
+                    ("Archived:", p.archived.then(|| "yes".to_string())),
+                    (
+                        "Repository:",
+                        p.repository.as_ref().map(|r| r.display().to_string()),
+                    ),
+                ];
+                for (label, value) in fields {
+                    if let Some(value) = value {
+                        println!("{:<13}{}", label, value.trim_end());
+                    }
+                }
+            }
+            let today = Local::now().date_naive();
+            if let Some(status) = log.budget_status(&name, today) {
+                println!("{:<13}{}", "Budget:", status.describe(format));
+            }
+            let days: Vec<NaiveDate> = log
+                .entries(NaiveDate::MIN..=NaiveDate::MAX)
+                .filter(|(_, e)| e.project_name == name)
+                .map(|(d, _)| *d)
+                .collect();
+            let tracked = log.tracked_time_on(&name, NaiveDate::MIN..=NaiveDate::MAX);
+            match (days.first(), days.last()) {
+                (Some(first), Some(last)) => println!(
+                    "{:<13}{} ({} – {})",
+                    "Tracked:",
+                    format.format(&tracked),
+                    first,
+                    last
+                ),
+                _ => println!("{:<13}{}", "Tracked:", format.format(&tracked)),
+            }
+            Ok(false)
+        }
+        ProjectAction::Budget {
+            name, remove: true, ..
+        } => {
+            let name = known_project(log, &name)?;
+            if log.projects.get_or_insert(&name).budget.take().is_none() {
+                return Err(TrackieError::new(
+                    format!("Project {} has no budget.", name).as_str(),
+                ));
+            }
+            println!("Removed the budget of project {}", name.italic());
+            Ok(true)
+        }
+        ProjectAction::Budget {
+            name,
+            budget: Some(limit),
+            per,
+            ..
+        } => {
+            let name = known_project(log, &name)?;
+            log.projects.get_or_insert(&name).budget = Some(Budget { limit, per });
+            println!(
+                "Set the budget of project {} to {} {}",
+                name.italic(),
+                format.format(&limit),
+                per
+            );
+            Ok(true)
+        }
+        ProjectAction::Budget { name, .. } => {
+            let name = known_project(log, &name)?;
+            match log.budget_status(&name, Local::now().date_naive()) {
+                Some(status) => println!("{}", status.describe(format)),
+                None => {
+                    return Err(TrackieError::new(
+                        format!("Project {} has no budget.", name).as_str(),
+                    ))
+                }
+            }
+            Ok(false)
+        }
+    }
+}
+
+/// Resolves an alias and makes sure that the project has been tracked or registered.
+fn known_project(log: &TimeLog, name: &str) -> Result<String, TrackieError> {
+    let name = log.projects.resolve(name);
+    match log.project_names().contains(name) {
+        true => Ok(name.to_string()),
+        false => Err(TrackieError::new(
+            format!("Unknown project {}.", name).as_str(),
+        )),
+    }
+}
+
+/// Warns if the consumed budget reached one of the configured thresholds since `before`.
+fn warn_budget(
+    project: &str,
+    status: &BudgetStatus,
+    before: Duration,
+    config: &Config,
+    format: DurationFormat,
+) {
+    if let Some(warning) = budget_warning(project, status, before, config, format) {
+        println!("{} {}", "WARN:".yellow(), warning);
+    }
+}
+
+/// The warning that the consumption of a project's budget passed an alert threshold since it
+/// was at `before`, if it did.
+fn budget_warning(
+    project: &str,
+    status: &BudgetStatus,
+    before: Duration,
+    config: &Config,
+    format: DurationFormat,
+) -> Option<String> {
+    let threshold = status.passed_threshold(config.budget_alerts(), before)?;
+    Some(format!(
+        "Project {} reached {}% of its budget: {}",
+        project,
+        threshold,
+        status.describe(format)
+    ))
+}
+
+/// The file to write to if given, stdout otherwise.
+fn output(path: &Option<PathBuf>) -> io::Result<Box<dyn Write>> {
+    match path {
+        Some(path) => {
+            // Escape sequences for colours only make sense on a terminal.
+            colored::control::set_override(false);
+            Ok(Box::new(BufWriter::new(File::create(path)?)))
+        }
+        None => Ok(Box::new(io::stdout().lock())),
+    }
+}
+
+/// Prints the script registering trackie's dynamic completions, which complete project names
+/// by calling trackie with `COMPLETE=<shell>`.
+fn print_completions(shell: Shell, cmd: &mut Command) -> Result<(), TrackieError> {
+    let completer: &dyn EnvCompleter = match shell {
+        Shell::Bash => &Bash,
+        Shell::Elvish => &Elvish,
+        Shell::Fish => &Fish,
+        Shell::PowerShell => &Powershell,
+        Shell::Zsh => &Zsh,
+        _ => {
+            generate(shell, cmd, cmd.get_name().to_string(), &mut io::stdout());
+            return Ok(());
+        }
+    };
+    let name = cmd.get_name().to_string();
+    completer.write_registration("COMPLETE", &name, &name, &name, &mut io::stdout())?;
+    Ok(())
 }
 
 #[derive(Debug)]
 pub struct TrackieError {
     msg: String,
     pub print_as_error: bool,
+    pub exit_code: i32,
 }
 
 impl TrackieError {
@@ -131,6 +983,7 @@ impl TrackieError {
         TrackieError {
             msg: msg.to_string(),
             print_as_error: true,
+            exit_code: 1,
         }
     }
 }
@@ -147,6 +1000,12 @@ impl From<Box<dyn Error>> for TrackieError {
     }
 }
 
+impl From<io::Error> for TrackieError {
+    fn from(e: io::Error) -> Self {
+        TrackieError::new(e.to_string().as_str())
+    }
+}
+
 impl From<serde_json::Error> for TrackieError {
     fn from(e: serde_json::Error) -> Self {
         TrackieError::new(e.to_string().as_str())
@@ -160,8 +1019,8 @@ mod tests {
     use clap_complete::Shell;
 
     use crate::cli::{
-        CompletionCommand, EmptyCommand, Opts, StatusCommand, Subcommand, TimingCommand,
-        DEFAULT_EMPTY_STATUS_MSG,
+        CompletionCommand, IdleOptions, Opts, ResumeCommand, StatusCommand, StopCommand,
+        Subcommand, TimingCommand, DEFAULT_EMPTY_STATUS_MSG,
     };
     use crate::persistence::FileHandler;
     use crate::run_app;
@@ -175,6 +1034,10 @@ mod tests {
                 sub_cmd: Subcommand::Status(StatusCommand {
                     format: None,
                     fallback: Some("Foo".to_string()),
+                    duration_format: None,
+                    check_project: false,
+                    json: false,
+                    idle: IdleOptions::default(),
                 }),
             },
             &mut handler,
@@ -191,6 +1054,10 @@ mod tests {
                 sub_cmd: Subcommand::Status(StatusCommand {
                     format: None,
                     fallback: None,
+                    duration_format: None,
+                    check_project: false,
+                    json: false,
+                    idle: IdleOptions::default(),
                 }),
             },
             &mut handler,
@@ -205,7 +1072,9 @@ mod tests {
         run_app(
             Opts {
                 sub_cmd: Subcommand::Start(TimingCommand {
-                    project_name: "Foo".to_string(),
+                    project_name: Some("Foo".to_string()),
+                    note: None,
+                    tags: Vec::new(),
                 }),
             },
             &mut handler,
@@ -222,7 +1091,9 @@ mod tests {
 
         let x = run_app(
             Opts {
-                sub_cmd: Subcommand::Resume(EmptyCommand {}),
+                sub_cmd: Subcommand::Resume(ResumeCommand {
+                    idle: IdleOptions::default(),
+                }),
             },
             &mut handler,
         );
@@ -231,14 +1102,19 @@ mod tests {
         run_app(
             Opts {
                 sub_cmd: Subcommand::Start(TimingCommand {
-                    project_name: "Foo".to_string(),
+                    project_name: Some("Foo".to_string()),
+                    note: None,
+                    tags: Vec::new(),
                 }),
             },
             &mut handler,
         )?;
         run_app(
             Opts {
-                sub_cmd: Subcommand::Stop(EmptyCommand {}),
+                sub_cmd: Subcommand::Stop(StopCommand {
+                    duration_format: None,
+                    idle: IdleOptions::default(),
+                }),
             },
             &mut handler,
         )?;
@@ -248,6 +1124,10 @@ mod tests {
                 sub_cmd: Subcommand::Status(StatusCommand {
                     fallback: None,
                     format: None,
+                    duration_format: None,
+                    check_project: false,
+                    json: false,
+                    idle: IdleOptions::default(),
                 }),
             },
             &mut handler,
@@ -256,7 +1136,9 @@ mod tests {
 
         run_app(
             Opts {
-                sub_cmd: Subcommand::Resume(EmptyCommand {}),
+                sub_cmd: Subcommand::Resume(ResumeCommand {
+                    idle: IdleOptions::default(),
+                }),
             },
             &mut handler,
         )?;
@@ -266,6 +1148,10 @@ mod tests {
                 sub_cmd: Subcommand::Status(StatusCommand {
                     fallback: None,
                     format: None,
+                    duration_format: None,
+                    check_project: false,
+                    json: false,
+                    idle: IdleOptions::default(),
                 }),
             },
             &mut handler,
@@ -281,7 +1167,9 @@ mod tests {
         run_app(
             Opts {
                 sub_cmd: Subcommand::Start(TimingCommand {
-                    project_name: "Foo".to_string(),
+                    project_name: Some("Foo".to_string()),
+                    note: None,
+                    tags: Vec::new(),
                 }),
             },
             &mut handler,
@@ -292,6 +1180,10 @@ mod tests {
                 sub_cmd: Subcommand::Status(StatusCommand {
                     format: None,
                     fallback: None,
+                    duration_format: None,
+                    check_project: false,
+                    json: false,
+                    idle: IdleOptions::default(),
                 }),
             },
             &mut handler,
@@ -305,7 +1197,9 @@ mod tests {
         run_app(
             Opts {
                 sub_cmd: Subcommand::Start(TimingCommand {
-                    project_name: "Foo".to_string(),
+                    project_name: Some("Foo".to_string()),
+                    note: None,
+                    tags: Vec::new(),
                 }),
             },
             &mut handler,
@@ -313,7 +1207,10 @@ mod tests {
 
         run_app(
             Opts {
-                sub_cmd: Subcommand::Stop(EmptyCommand {}),
+                sub_cmd: Subcommand::Stop(StopCommand {
+                    duration_format: None,
+                    idle: IdleOptions::default(),
+                }),
             },
             &mut handler,
         )?;
@@ -323,6 +1220,10 @@ mod tests {
                 sub_cmd: Subcommand::Status(StatusCommand {
                     format: None,
                     fallback: None,
+                    duration_format: None,
+                    check_project: false,
+                    json: false,
+                    idle: IdleOptions::default(),
                 }),
             },
             &mut handler,
@@ -332,7 +1233,10 @@ mod tests {
 
         let second_stop = run_app(
             Opts {
-                sub_cmd: Subcommand::Stop(EmptyCommand {}),
+                sub_cmd: Subcommand::Stop(StopCommand {
+                    duration_format: None,
+                    idle: IdleOptions::default(),
+                }),
             },
             &mut handler,
         );
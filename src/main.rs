@@ -1,4 +1,5 @@
-use clap::Parser;
+use clap::{CommandFactory, Parser};
+use clap_complete::CompleteEnv;
 use colored::Colorize;
 use trackie::cli::Opts;
 use trackie::persistence::FsFileHandler;
@@ -6,6 +7,7 @@ use trackie::run_app;
 
 pub fn main() {
     include_str!("../Cargo.toml");
+    CompleteEnv::with_factory(Opts::command).complete();
     let mut fs = FsFileHandler::new();
     if let Err(e) = run_app(Opts::parse(), &mut fs) {
         if e.print_as_error {
@@ -13,6 +15,6 @@ pub fn main() {
         } else {
             println!("{}", e);
         }
-        std::process::exit(1);
+        std::process::exit(e.exit_code);
     }
 }
@@ -1,15 +1,26 @@
-use crate::cli::ENV_TRACKIE_CONFIG;
+use crate::cli::{ENV_TRACKIE_CONFIG, ENV_TRACKIE_SETTINGS};
+use crate::config::Config;
 use crate::time_log::TimeLog;
 use std::env;
 use std::error::Error;
-use std::fs::{create_dir_all, read_to_string, rename, OpenOptions};
+use std::fs::{create_dir_all, metadata, read_to_string, rename, OpenOptions};
 use std::io::Write;
 use std::path::PathBuf;
+use std::time::SystemTime;
 
 pub trait FileHandler {
     fn read_file(&self) -> Result<Option<String>, Box<dyn Error>>;
 
     fn write_file(&mut self, content: &str) -> Result<(), Box<dyn Error>>;
+
+    fn read_config(&self) -> Result<Option<String>, Box<dyn Error>> {
+        Ok(None)
+    }
+
+    /// When the data file was last modified, if that is known.
+    fn modified(&self) -> Option<SystemTime> {
+        None
+    }
 }
 
 pub struct FsFileHandler {}
@@ -31,6 +42,13 @@ impl FsFileHandler {
             .unwrap()
     }
 
+    fn settings_file() -> Option<PathBuf> {
+        env::var(ENV_TRACKIE_SETTINGS)
+            .ok()
+            .map(Into::<PathBuf>::into)
+            .or_else(|| dirs::config_dir().map(|i| i.join("trackie").join("config.toml")))
+    }
+
     fn move_legacy_config_file() -> Result<(), Box<dyn Error>> {
         if env::var(ENV_TRACKIE_CONFIG).is_ok() {
             return Ok(());
@@ -77,16 +95,32 @@ impl FileHandler for FsFileHandler {
         let trackie_file = Self::trackie_file();
         create_dir_all(&trackie_file.parent().unwrap())?;
 
+        // Write to a temporary file first, so that the log is never left half-written.
+        let tmp_file = trackie_file.with_extension("json.tmp");
         let mut f = OpenOptions::new()
             .write(true)
             .create(true)
             .truncate(true)
-            .open(trackie_file)?;
+            .open(&tmp_file)?;
 
         f.write_all(content.as_bytes())?;
-        f.flush()?;
+        f.sync_all()?;
+        rename(tmp_file, trackie_file)?;
         Ok(())
     }
+
+    fn read_config(&self) -> Result<Option<String>, Box<dyn Error>> {
+        match Self::settings_file() {
+            Some(f) if f.is_file() => Ok(Some(read_to_string(f)?)),
+            _ => Ok(None),
+        }
+    }
+
+    fn modified(&self) -> Option<SystemTime> {
+        metadata(Self::trackie_file())
+            .and_then(|m| m.modified())
+            .ok()
+    }
 }
 
 pub fn load_or_create_log(handler: &dyn FileHandler) -> Result<TimeLog, Box<dyn Error>> {
@@ -100,3 +134,10 @@ pub fn save_log(handler: &mut dyn FileHandler, log: &TimeLog) -> Result<(), Box<
     let content = serde_json::to_string(log)?;
     handler.write_file(&content)
 }
+
+pub fn load_config(handler: &dyn FileHandler) -> Result<Config, Box<dyn Error>> {
+    match handler.read_config()? {
+        Some(content) => Ok(Config::from_toml(&content)?),
+        None => Ok(Config::default()),
+    }
+}
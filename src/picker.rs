@@ -0,0 +1,219 @@
+use crate::TrackieError;
+use crossterm::cursor::{MoveToColumn, MoveUp};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::style::{Attribute, Print, SetAttribute};
+use crossterm::terminal::{self, Clear, ClearType};
+use crossterm::{queue, QueueableCommand};
+use std::io::{self, IsTerminal, Write};
+
+/// The number of matching projects shown at once.
+const VISIBLE_ROWS: usize = 10;
+const PROMPT: &str = "Project: ";
+
+/// Lets the user pick one of `projects` in the terminal by typing parts of its name.
+/// `projects` is expected to be ordered by relevance, which is kept for equally good matches.
+///
+/// Returns `None` if the user aborted the selection.
+pub fn pick_project(projects: &[&str]) -> Result<Option<String>, TrackieError> {
+    if !io::stdin().is_terminal() {
+        return Err(TrackieError::new(
+            "No project given. The project picker needs an interactive terminal.",
+        ));
+    }
+    if projects.is_empty() {
+        return Err(TrackieError::new(
+            "No project given and no projects to pick from. Start one via `trackie start <project>`.",
+        ));
+    }
+
+    let mut picker = Picker::new(projects);
+    let mut out = io::stderr();
+    terminal::enable_raw_mode()?;
+    let result = run(&mut picker, &mut out);
+    queue!(out, MoveToColumn(0), Clear(ClearType::FromCursorDown))?;
+    out.flush()?;
+    terminal::disable_raw_mode()?;
+    Ok(result?.map(str::to_string))
+}
+
+fn run<'a>(picker: &mut Picker<'a>, out: &mut impl Write) -> io::Result<Option<&'a str>> {
+    loop {
+        picker.draw(out)?;
+        if let Event::Key(key) = event::read()? {
+            if key.kind == KeyEventKind::Release {
+                continue;
+            }
+            if let Some(outcome) = picker.handle_key(key) {
+                return Ok(outcome);
+            }
+        }
+    }
+}
+
+/// The state of the picker, independent of the terminal.
+struct Picker<'a> {
+    projects: &'a [&'a str],
+    query: String,
+    matches: Vec<&'a str>,
+    selected: usize,
+}
+
+impl<'a> Picker<'a> {
+    fn new(projects: &'a [&'a str]) -> Self {
+        Self {
+            projects,
+            query: String::new(),
+            matches: projects.to_vec(),
+            selected: 0,
+        }
+    }
+
+    /// Applies a key press. Returns the outcome of the selection once it is finished.
+    fn handle_key(&mut self, key: KeyEvent) -> Option<Option<&'a str>> {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Esc => return Some(None),
+            KeyCode::Char('c' | 'd') if ctrl => return Some(None),
+            KeyCode::Enter if !self.matches.is_empty() => {
+                return Some(Some(self.matches[self.selected]))
+            }
+            KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Char('p') if ctrl => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down | KeyCode::Tab => self.select_next(),
+            KeyCode::Char('n') if ctrl => self.select_next(),
+            KeyCode::Char('u') if ctrl => self.set_query(String::new()),
+            KeyCode::Backspace => {
+                let mut query = self.query.clone();
+                query.pop();
+                self.set_query(query);
+            }
+            KeyCode::Char(c) if !ctrl => self.set_query(format!("{}{}", self.query, c)),
+            _ => {}
+        }
+        None
+    }
+
+    fn select_next(&mut self) {
+        if self.selected + 1 < self.matches.len() {
+            self.selected += 1;
+        }
+    }
+
+    fn set_query(&mut self, query: String) {
+        self.query = query;
+        self.matches = filter(&self.query, self.projects);
+        self.selected = 0;
+    }
+
+    fn draw(&self, out: &mut impl Write) -> io::Result<()> {
+        queue!(
+            out,
+            MoveToColumn(0),
+            Clear(ClearType::FromCursorDown),
+            Print(PROMPT),
+            Print(&self.query)
+        )?;
+        let first = (self.selected + 1).saturating_sub(VISIBLE_ROWS);
+        let rows = &self.matches[first..self.matches.len().min(first + VISIBLE_ROWS)];
+        for (idx, project) in rows.iter().enumerate() {
+            out.queue(Print("\r\n"))?;
+            if first + idx == self.selected {
+                queue!(
+                    out,
+                    SetAttribute(Attribute::Reverse),
+                    Print(format!("> {}", project)),
+                    SetAttribute(Attribute::Reset)
+                )?;
+            } else {
+                out.queue(Print(format!("  {}", project)))?;
+            }
+        }
+        if !rows.is_empty() {
+            out.queue(MoveUp(rows.len() as u16))?;
+        }
+        let column = PROMPT.chars().count() + self.query.chars().count();
+        out.queue(MoveToColumn(column as u16))?;
+        out.flush()
+    }
+}
+
+/// The projects matching `query`, the best matches first.
+pub fn filter<'a>(query: &str, projects: &[&'a str]) -> Vec<&'a str> {
+    let mut matches: Vec<(u32, &str)> = projects
+        .iter()
+        .filter_map(|p| fuzzy_score(query, p).map(|score| (score, *p)))
+        .collect();
+    matches.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+    matches.into_iter().map(|(_, p)| p).collect()
+}
+
+/// Scores how well `candidate` matches `query`, if it contains all characters of `query` in
+/// order. Consecutive characters and characters at the start of a word score higher.
+fn fuzzy_score(query: &str, candidate: &str) -> Option<u32> {
+    let mut query = query.chars().flat_map(char::to_lowercase).peekable();
+    let mut score = 0;
+    let mut previous: Option<char> = None;
+    let mut previous_matched = false;
+    for c in candidate.chars() {
+        let Some(q) = query.peek() else { break };
+        let matched = c.to_lowercase().eq(std::iter::once(*q));
+        if matched {
+            score += 1;
+            if previous_matched {
+                score += 2;
+            }
+            if previous.is_none_or(|p| !p.is_alphanumeric()) {
+                score += 3;
+            }
+            query.next();
+        }
+        previous = Some(c);
+        previous_matched = matched;
+    }
+    query.peek().is_none().then_some(score)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fuzzy_scores() {
+        assert!(fuzzy_score("", "website").is_some());
+        assert!(fuzzy_score("wbs", "website").is_some());
+        assert!(fuzzy_score("WEB", "website").is_some());
+        assert!(fuzzy_score("sw", "website").is_none());
+        assert!(fuzzy_score("web", "website") > fuzzy_score("wbe", "website/backend"));
+        assert!(fuzzy_score("ab", "acme/backend") > fuzzy_score("ab", "table"));
+    }
+
+    #[test]
+    fn filter_keeps_order_of_equal_matches() {
+        let projects = ["support", "acme/web", "website", "acme/api"];
+        assert_eq!(filter("", &projects), projects);
+        assert_eq!(filter("web", &projects), ["acme/web", "website"]);
+        assert_eq!(filter("acme", &projects), ["acme/web", "acme/api"]);
+        assert!(filter("xyz", &projects).is_empty());
+    }
+
+    #[test]
+    fn select_with_keys() {
+        let projects = ["support", "acme/web", "website"];
+        let mut picker = Picker::new(&projects);
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+
+        assert_eq!(picker.handle_key(key(KeyCode::Down)), None);
+        assert_eq!(picker.matches[picker.selected], "acme/web");
+        for c in "wz".chars() {
+            picker.handle_key(key(KeyCode::Char(c)));
+        }
+        assert_eq!(picker.handle_key(key(KeyCode::Enter)), None);
+        picker.handle_key(key(KeyCode::Backspace));
+        picker.handle_key(key(KeyCode::Down));
+        assert_eq!(
+            picker.handle_key(key(KeyCode::Enter)),
+            Some(Some("website"))
+        );
+        assert_eq!(picker.handle_key(key(KeyCode::Esc)), Some(None));
+    }
+}
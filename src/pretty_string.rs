@@ -1,15 +1,115 @@
 use chrono::Duration;
+use clap::ValueEnum;
+use serde::Deserialize;
 
 const MINS_IN_HOUR: i64 = 60;
+const SECS_IN_HOUR: f64 = 3600.0;
 
-pub trait PrettyString {
-    fn to_pretty_string(&self) -> String;
+/// The ways a [Duration] can be printed.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DurationFormat {
+    /// Hours and minutes, e.g. `01h 45m`
+    #[default]
+    Hm,
+    /// Decimal hours, e.g. `1.75`
+    Decimal,
+    /// A clock-like format, e.g. `1:45`
+    Clock,
+    /// An ISO 8601 duration, e.g. `PT1H45M`
+    Iso8601,
+    /// Written out, e.g. `1 hour 45 minutes`
+    Human,
 }
 
-impl PrettyString for Duration {
+impl DurationFormat {
+    pub fn format(&self, d: &Duration) -> String {
+        let sign = if *d < Duration::zero() { "-" } else { "" };
+        let secs = d.num_seconds().abs();
+        let (hours, mins) = (secs / 3600, secs / 60 % MINS_IN_HOUR);
 
-    fn to_pretty_string(&self) -> String {
-        let remaining_min = self.num_minutes() - (self.num_hours() * MINS_IN_HOUR);
-        format!("{:02}h {:02}m", self.num_hours(), remaining_min)
+        match self {
+            DurationFormat::Hm => format!("{}{:02}h {:02}m", sign, hours, mins),
+            DurationFormat::Decimal => format!("{}{:.2}", sign, secs as f64 / SECS_IN_HOUR),
+            DurationFormat::Clock => format!("{}{}:{:02}", sign, hours, mins),
+            DurationFormat::Iso8601 => format!("{}{}", sign, iso8601(secs)),
+            DurationFormat::Human => format!("{}{}", sign, human(secs)),
+        }
+    }
+}
+
+fn iso8601(secs: i64) -> String {
+    if secs == 0 {
+        return "PT0S".to_string();
+    }
+    let mut result = String::from("PT");
+    let parts = [(secs / 3600, 'H'), (secs / 60 % 60, 'M'), (secs % 60, 'S')];
+    for (value, unit) in parts.iter().filter(|(v, _)| *v > 0) {
+        result.push_str(&format!("{}{}", value, unit));
+    }
+    result
+}
+
+fn human(secs: i64) -> String {
+    let parts = [
+        (secs / 86400, "day"),
+        (secs / 3600 % 24, "hour"),
+        (secs / 60 % 60, "minute"),
+    ];
+    let words: Vec<String> = parts
+        .iter()
+        .filter(|(v, _)| *v > 0)
+        .map(|(v, unit)| format!("{} {}{}", v, unit, if *v == 1 { "" } else { "s" }))
+        .collect();
+
+    match (words.is_empty(), secs) {
+        (true, 1) => "1 second".to_string(),
+        (true, _) => format!("{} seconds", secs),
+        (false, _) => words.join(" "),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dur() -> Duration {
+        Duration::hours(1) + Duration::minutes(45)
+    }
+
+    #[test]
+    fn format_hm() {
+        assert_eq!(DurationFormat::Hm.format(&dur()), "01h 45m");
+        assert_eq!(DurationFormat::Hm.format(&-dur()), "-01h 45m");
+    }
+
+    #[test]
+    fn format_decimal_and_clock() {
+        assert_eq!(DurationFormat::Decimal.format(&dur()), "1.75");
+        assert_eq!(DurationFormat::Clock.format(&dur()), "1:45");
+        assert_eq!(DurationFormat::Clock.format(&-dur()), "-1:45");
+    }
+
+    #[test]
+    fn format_iso8601() {
+        assert_eq!(DurationFormat::Iso8601.format(&dur()), "PT1H45M");
+        assert_eq!(DurationFormat::Iso8601.format(&Duration::zero()), "PT0S");
+        assert_eq!(
+            DurationFormat::Iso8601.format(&Duration::seconds(61)),
+            "PT1M1S"
+        );
+    }
+
+    #[test]
+    fn format_human() {
+        assert_eq!(DurationFormat::Human.format(&dur()), "1 hour 45 minutes");
+        assert_eq!(
+            DurationFormat::Human.format(&(Duration::days(2) + Duration::minutes(1))),
+            "2 days 1 minute"
+        );
+        assert_eq!(
+            DurationFormat::Human.format(&Duration::seconds(30)),
+            "30 seconds"
+        );
     }
 }
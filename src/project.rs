@@ -0,0 +1,497 @@
+use crate::duration::{deserialize_seconds, serialize_seconds};
+use crate::pretty_string::DurationFormat;
+use crate::report_creator::serialize_duration;
+use crate::TrackieError;
+use chrono::{Datelike, Duration, NaiveDate};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::ops::RangeInclusive;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// The metadata of projects, keyed by their name. Stored inside the [TimeLog](crate::time_log::TimeLog).
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Projects {
+    projects: BTreeMap<String, Project>,
+}
+
+impl Projects {
+    pub fn is_empty(&self) -> bool {
+        self.projects.is_empty()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Project> {
+        self.projects.get(name)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &Project)> {
+        self.projects.iter()
+    }
+
+    /// The project that has `alias` as one of its aliases.
+    pub fn alias_of(&self, alias: &str) -> Option<&str> {
+        self.projects
+            .iter()
+            .find(|(_, p)| p.aliases.iter().any(|a| a == alias))
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// Returns the name of the project that `name` refers to, resolving aliases.
+    pub fn resolve<'a>(&'a self, name: &'a str) -> &'a str {
+        self.alias_of(name).unwrap_or(name)
+    }
+
+    /// Moves the metadata of a project and its subprojects to a new name. If there already is
+    /// metadata under the new name, the two are merged.
+    pub fn rename(&mut self, from: &str, to: &str) {
+        let names: Vec<String> = self
+            .projects
+            .keys()
+            .filter(|name| is_within(name, from))
+            .cloned()
+            .collect();
+        for name in names {
+            let project = self.projects.remove(&name).unwrap();
+            let new_name = rebase(&name, from, to).unwrap();
+            match self.projects.get_mut(&new_name) {
+                Some(existing) => existing.absorb(project),
+                None => {
+                    self.projects.insert(new_name, project);
+                }
+            }
+        }
+    }
+
+    /// Returns the metadata of the project, creating it if the project has none yet.
+    pub fn get_or_insert(&mut self, name: &str) -> &mut Project {
+        self.projects.entry(name.to_string()).or_default()
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Project {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// A CSS colour like `#4e79a7` or `teal`, used in charts.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client: Option<String>,
+    /// The hourly rate, used for invoices if the settings configure none.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rate: Option<f64>,
+    /// Archived projects are hidden from `project list`.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub archived: bool,
+    /// Other names that can be used to refer to the project.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub budget: Option<Budget>,
+    /// The local git repository the project is developed in.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repository: Option<PathBuf>,
+}
+
+/// The separators between the levels of hierarchical project names like `client/project/task`.
+pub const SEPARATORS: [char; 2] = ['/', ':'];
+
+/// Whether `name` is `ancestor` itself or one of its subprojects.
+pub fn is_within(name: &str, ancestor: &str) -> bool {
+    let mut segments = name.split(SEPARATORS);
+    ancestor
+        .split(SEPARATORS)
+        .all(|a| segments.next() == Some(a))
+}
+
+/// Replaces `from` in `name` by `to` if `name` is `from` or one of its subprojects, e.g.
+/// `new/web` for `old/web`.
+pub fn rebase(name: &str, from: &str, to: &str) -> Option<String> {
+    // Both separators are ASCII, so equal segments have equal byte lengths.
+    is_within(name, from).then(|| format!("{}{}", to, &name[from.len()..]))
+}
+
+/// The first `levels` levels of a hierarchical project name, e.g. `client/project` for
+/// `client/project/task` and two levels.
+pub fn ancestor(name: &str, levels: usize) -> &str {
+    match name.match_indices(SEPARATORS).nth(levels.saturating_sub(1)) {
+        Some((idx, _)) if levels > 0 => &name[..idx],
+        _ => name,
+    }
+}
+
+/// The last level of a hierarchical project name.
+pub fn base_name(name: &str) -> &str {
+    name.rsplit(SEPARATORS).next().unwrap_or(name)
+}
+
+/// Selects projects by name: `client/project` selects a single project, `client/*` a project
+/// together with all of its subprojects.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProjectPattern {
+    name: String,
+    subprojects: bool,
+}
+
+impl ProjectPattern {
+    pub fn matches(&self, name: &str) -> bool {
+        match self.subprojects {
+            true => is_within(name, &self.name),
+            false => name.split(SEPARATORS).eq(self.name.split(SEPARATORS)),
+        }
+    }
+}
+
+impl FromStr for ProjectPattern {
+    type Err = TrackieError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, subprojects) = match s.strip_suffix('*') {
+            Some(prefix) => match prefix.strip_suffix(SEPARATORS) {
+                Some(name) => (name, true),
+                None => {
+                    return Err(TrackieError::new(
+                        format!("Invalid project pattern '{}'. Use e.g. client/*.", s).as_str(),
+                    ))
+                }
+            },
+            None => (s, false),
+        };
+        Ok(ProjectPattern {
+            name: name.to_string(),
+            subprojects,
+        })
+    }
+}
+
+/// How `start` treats project names that were neither tracked nor registered before.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UnknownProjects {
+    /// Starts tracking a new project
+    #[default]
+    Allow,
+    /// Starts tracking, but suggests the closest existing project
+    Warn,
+    /// Refuses to track, suggesting the closest existing project
+    Refuse,
+}
+
+impl Project {
+    /// Takes over the aliases and all properties this project does not have of `other`.
+    fn absorb(&mut self, other: Project) {
+        self.description = self.description.take().or(other.description);
+        self.color = self.color.take().or(other.color);
+        self.client = self.client.take().or(other.client);
+        self.rate = self.rate.or(other.rate);
+        self.budget = self.budget.or(other.budget);
+        self.repository = self.repository.take().or(other.repository);
+        self.aliases.extend(other.aliases);
+    }
+}
+
+/// Whether `color` is a hex colour (`#rgb` or `#rrggbb`) or a named CSS colour.
+pub fn is_valid_color(color: &str) -> bool {
+    match color.strip_prefix('#') {
+        Some(hex) => [3, 6].contains(&hex.len()) && hex.chars().all(|c| c.is_ascii_hexdigit()),
+        None => !color.is_empty() && color.chars().all(|c| c.is_ascii_alphabetic()),
+    }
+}
+
+/// Finds the candidate that is most similar to `name`, if any is similar enough to be a typo.
+pub fn closest_match<'a>(name: &str, candidates: impl Iterator<Item = &'a str>) -> Option<&'a str> {
+    let max_distance = (name.chars().count() / 3).max(1);
+    candidates
+        .map(|c| (edit_distance(&name.to_lowercase(), &c.to_lowercase()), c))
+        .filter(|(d, _)| *d <= max_distance)
+        .min_by_key(|(d, _)| *d)
+        .map(|(_, c)| c)
+}
+
+/// The Levenshtein distance between two strings.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != *cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+/// The amount of time that may be spent on a project.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Budget {
+    #[serde(
+        rename = "seconds",
+        serialize_with = "serialize_seconds",
+        deserialize_with = "deserialize_seconds"
+    )]
+    pub limit: Duration,
+    #[serde(default)]
+    pub per: BudgetPeriod,
+}
+
+/// The period after which a budget starts anew.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum BudgetPeriod {
+    /// The budget applies to the whole lifetime of the project
+    #[default]
+    Total,
+    /// The budget applies to each calendar week
+    Week,
+    /// The budget applies to each calendar month
+    Month,
+}
+
+impl BudgetPeriod {
+    /// The days of the period that contains `date`.
+    pub fn range(&self, date: NaiveDate) -> RangeInclusive<NaiveDate> {
+        match self {
+            BudgetPeriod::Total => NaiveDate::MIN..=NaiveDate::MAX,
+            BudgetPeriod::Week => {
+                let week = date.week(chrono::Weekday::Mon);
+                week.first_day()..=week.last_day()
+            }
+            BudgetPeriod::Month => {
+                let first = date.with_day(1).unwrap();
+                let next = first + Duration::days(32);
+                first..=next.with_day(1).unwrap().pred_opt().unwrap()
+            }
+        }
+    }
+}
+
+impl Display for BudgetPeriod {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            BudgetPeriod::Total => "in total",
+            BudgetPeriod::Week => "this week",
+            BudgetPeriod::Month => "this month",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// How much of a [Budget] has been consumed in the current period.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct BudgetStatus {
+    #[serde(serialize_with = "serialize_duration")]
+    pub limit: Duration,
+    pub per: BudgetPeriod,
+    #[serde(serialize_with = "serialize_duration")]
+    pub consumed: Duration,
+    #[serde(serialize_with = "serialize_duration")]
+    pub remaining: Duration,
+}
+
+impl BudgetStatus {
+    pub fn new(budget: Budget, consumed: Duration) -> Self {
+        BudgetStatus {
+            limit: budget.limit,
+            per: budget.per,
+            consumed,
+            remaining: budget.limit - consumed,
+        }
+    }
+
+    /// The consumed share of the budget in percent.
+    pub fn percent(&self) -> f64 {
+        match self.limit.num_seconds() {
+            0 => 100.0,
+            limit => self.consumed.num_seconds() as f64 * 100.0 / limit as f64,
+        }
+    }
+
+    /// Returns the highest of the `thresholds` (in percent) that the consumed time reached, but
+    /// that was not yet reached by `before`.
+    pub fn passed_threshold(&self, thresholds: &[u32], before: Duration) -> Option<u32> {
+        let reached = |consumed: Duration, threshold: u32| {
+            consumed.num_seconds() * 100 >= self.limit.num_seconds() * threshold as i64
+        };
+        thresholds
+            .iter()
+            .copied()
+            .filter(|t| reached(self.consumed, *t) && !reached(before, *t))
+            .max()
+    }
+
+    pub fn describe(&self, format: DurationFormat) -> String {
+        format!(
+            "{} / {} {} ({:.0}%)",
+            format.format(&self.consumed),
+            format.format(&self.limit),
+            self.per,
+            self.percent()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, m, d).unwrap()
+    }
+
+    #[test]
+    fn period_ranges() {
+        assert_eq!(
+            BudgetPeriod::Week.range(date(9, 10)),
+            date(9, 7)..=date(9, 13)
+        );
+        assert_eq!(
+            BudgetPeriod::Month.range(date(12, 31)),
+            date(12, 1)..=date(12, 31)
+        );
+        assert_eq!(
+            BudgetPeriod::Month.range(date(2, 1)),
+            date(2, 1)..=date(2, 28)
+        );
+        assert!(BudgetPeriod::Total
+            .range(date(1, 1))
+            .contains(&NaiveDate::MIN));
+    }
+
+    #[test]
+    fn thresholds() {
+        let budget = Budget {
+            limit: Duration::hours(10),
+            per: BudgetPeriod::Total,
+        };
+        let status = BudgetStatus::new(budget, Duration::hours(9));
+
+        assert_eq!(status.percent(), 90.0);
+        assert_eq!(status.remaining, Duration::hours(1));
+        assert_eq!(
+            status.passed_threshold(&[80, 100], Duration::zero()),
+            Some(80)
+        );
+        assert_eq!(
+            status.passed_threshold(&[80, 100], Duration::hours(7)),
+            Some(80)
+        );
+        assert_eq!(
+            status.passed_threshold(&[80, 100], Duration::hours(8)),
+            None
+        );
+
+        let exceeded = BudgetStatus::new(budget, Duration::hours(11));
+        assert_eq!(exceeded.remaining, Duration::hours(-1));
+        assert_eq!(
+            exceeded.passed_threshold(&[80, 100], Duration::zero()),
+            Some(100)
+        );
+    }
+
+    #[test]
+    fn aliases() {
+        let mut projects = Projects::default();
+        projects.get_or_insert("client-x").aliases = vec!["cx".to_string()];
+
+        assert_eq!(projects.resolve("cx"), "client-x");
+        assert_eq!(projects.resolve("client-x"), "client-x");
+        assert_eq!(projects.resolve("other"), "other");
+
+        projects.rename("client-x", "client-y");
+        assert_eq!(projects.resolve("cx"), "client-y");
+    }
+
+    #[test]
+    fn closest_matches() {
+        let names = ["client-x", "website", "support"];
+        assert_eq!(
+            closest_match("clinet-x", names.into_iter()),
+            Some("client-x")
+        );
+        assert_eq!(closest_match("Websit", names.into_iter()), Some("website"));
+        assert_eq!(closest_match("holiday", names.into_iter()), None);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+    }
+
+    #[test]
+    fn hierarchy() {
+        assert!(is_within("client/web/frontend", "client"));
+        assert!(is_within("client:web", "client/web"));
+        assert!(is_within("client", "client"));
+        assert!(!is_within("clients/web", "client"));
+        assert!(!is_within("client", "client/web"));
+
+        assert_eq!(ancestor("client/web/frontend", 1), "client");
+        assert_eq!(ancestor("client/web:frontend", 2), "client/web");
+        assert_eq!(ancestor("client/web", 3), "client/web");
+        assert_eq!(base_name("client/web:frontend"), "frontend");
+        assert_eq!(base_name("client"), "client");
+    }
+
+    #[test]
+    fn rebase_subprojects() {
+        assert_eq!(rebase("old", "old", "new").as_deref(), Some("new"));
+        assert_eq!(rebase("old:web", "old", "a/b").as_deref(), Some("a/b:web"));
+        assert_eq!(rebase("older", "old", "new"), None);
+
+        let mut projects = Projects::default();
+        projects.get_or_insert("old/web").client = Some("ACME".to_string());
+        projects.get_or_insert("old/web").aliases = vec!["w".to_string()];
+        projects.get_or_insert("new/web").client = Some("Other".to_string());
+        projects.rename("old", "new");
+
+        let web = projects.get("new/web").unwrap();
+        assert_eq!(web.client.as_deref(), Some("Other"));
+        assert_eq!(web.aliases, ["w"]);
+        assert!(projects.get("old/web").is_none());
+    }
+
+    #[test]
+    fn project_patterns() {
+        let all: ProjectPattern = "client/*".parse().unwrap();
+        assert!(all.matches("client"));
+        assert!(all.matches("client:web"));
+        assert!(!all.matches("other/web"));
+
+        let single: ProjectPattern = "client/web".parse().unwrap();
+        assert!(single.matches("client:web"));
+        assert!(!single.matches("client/web/frontend"));
+
+        assert!("client*".parse::<ProjectPattern>().is_err());
+    }
+
+    #[test]
+    fn colors() {
+        assert!(is_valid_color("#4e79a7"));
+        assert!(is_valid_color("#fff"));
+        assert!(is_valid_color("teal"));
+        assert!(!is_valid_color("#12345"));
+        assert!(!is_valid_color("red\" onload=\""));
+    }
+
+    #[test]
+    fn serialize_budget() {
+        let mut projects = Projects::default();
+        projects.get_or_insert("foo").budget = Some(Budget {
+            limit: Duration::hours(40),
+            per: BudgetPeriod::Month,
+        });
+
+        let json = serde_json::to_string(&projects).unwrap();
+        assert_eq!(
+            json,
+            r#"{"foo":{"budget":{"seconds":144000,"per":"month"}}}"#
+        );
+        let parsed: Projects = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            parsed.get("foo").unwrap().budget,
+            projects.get("foo").unwrap().budget
+        );
+    }
+}
@@ -1,12 +1,19 @@
-use crate::pretty_string::PrettyString;
+use crate::calendar::Targets;
+use crate::csv::CsvWriter;
+use crate::git::{Commit, RepositoryLog};
+use crate::pretty_string::DurationFormat;
+use crate::project::{ancestor, base_name, is_within, BudgetStatus, ProjectPattern, SEPARATORS};
+use crate::rounding::{Rounding, RoundingScope};
 use crate::time_log::{LogEntry, TimeLog};
-use chrono::{Date, Duration, Local, NaiveDate};
+use chrono::{Date, Datelike, Duration, Local, NaiveDate};
 use colored::Colorize;
 use serde::{Serialize, Serializer};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::fmt::{Display, Formatter};
 use std::hash::Hash;
-use std::ops::{Add, Range};
+use std::io;
+use std::io::Write;
+use std::ops::{Add, Range, RangeInclusive};
 
 type GroupBy<'a, K> = HashMap<K, Vec<&'a LogEntry>>;
 
@@ -14,6 +21,12 @@ const ARROW: &str = "❯";
 
 pub struct ReportCreator<'a> {
     time_log: &'a TimeLog,
+    rounding: Option<Rounding>,
+    duration_format: DurationFormat,
+    targets: Option<Targets>,
+    depth: Option<u32>,
+    project: Option<ProjectPattern>,
+    repositories: Option<Vec<RepositoryLog>>,
 }
 
 #[derive(Serialize)]
@@ -21,25 +34,253 @@ pub struct DateRangeReport {
     pub range: Range<NaiveDate>,
     #[serde(serialize_with = "serialize_duration", rename = "total")]
     pub total_duration: Duration,
+    #[serde(
+        serialize_with = "serialize_opt_duration",
+        rename = "rounded_total",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub rounded_duration: Option<Duration>,
     pub days: Vec<DayReport>,
+    #[serde(skip)]
+    pub duration_format: DurationFormat,
 }
 
 impl DateRangeReport {
-    fn new(range: Range<Date<Local>>, days: Vec<DayReport>) -> Self {
-        Self {
-            range: range.start.naive_local()..range.end.naive_local(),
-            total_duration: days
+    fn new(
+        range: Range<Date<Local>>,
+        days: Vec<DayReport>,
+        rounding: Option<Rounding>,
+        duration_format: DurationFormat,
+    ) -> Self {
+        let total_duration = days
+            .iter()
+            .map(|r| r.total_duration)
+            .fold(Duration::zero(), |a, b| a.add(b));
+        let rounded_duration = rounding.map(|r| match r.scope {
+            RoundingScope::Report => r.round(total_duration),
+            _ => days
                 .iter()
-                .map(|r| r.total_duration)
+                .filter_map(|d| d.rounded_duration)
                 .fold(Duration::zero(), |a, b| a.add(b)),
+        });
+        Self {
+            range: range.start.naive_local()..range.end.naive_local(),
+            total_duration,
+            rounded_duration,
             days,
+            duration_format,
+        }
+    }
+}
+
+impl DateRangeReport {
+    /// Writes one CSV row per project and day. Durations are given in minutes.
+    pub fn write_csv<W: Write>(&self, w: &mut CsvWriter<W>, header: bool) -> io::Result<()> {
+        let projects = || {
+            self.days
+                .iter()
+                .flat_map(|d| d.leaves().map(move |p| (d, p)))
+        };
+        let rounded = projects().any(|(_, p)| p.rounded.is_some());
+
+        if header {
+            let mut fields = vec!["date", "project", "duration"];
+            if rounded {
+                fields.push("rounded");
+            }
+            w.write_record(&fields)?;
+        }
+        for (day, p) in projects() {
+            let mut fields = vec![
+                day.date.to_string(),
+                p.project.clone(),
+                p.duration.num_minutes().to_string(),
+            ];
+            if rounded {
+                fields.push(p.rounded.unwrap_or(p.duration).num_minutes().to_string());
+            }
+            w.write_record(&fields)?;
+        }
+        Ok(())
+    }
+}
+
+impl DateRangeReport {
+    /// Writes an Org table with a row per day, followed by a row per project of that day.
+    pub fn write_org<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        let mut rows = vec![
+            TableRow::Cells(self.header(&["Date", "Project"])),
+            TableRow::Rule,
+        ];
+        for day in &self.days {
+            let date = day.date.format("%a. %F").to_string();
+            rows.push(TableRow::Cells(self.cells(
+                vec![date, String::new()],
+                day.total_duration,
+                day.rounded_duration,
+            )));
+            for p in day.leaves() {
+                rows.push(TableRow::Cells(self.cells(
+                    vec![String::new(), escape_org(&p.project)],
+                    p.duration,
+                    p.rounded,
+                )));
+            }
+        }
+        rows.push(TableRow::Rule);
+        rows.push(TableRow::Cells(self.cells(
+            vec!["Total".to_string(), String::new()],
+            self.total_duration,
+            self.rounded_duration,
+        )));
+        write_table(out, &rows, 2)
+    }
+
+    /// Writes a GitHub flavored Markdown table per day, followed by the total of all days.
+    pub fn write_markdown<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        for day in &self.days {
+            writeln!(out, "### {}", day.date.format("%a. %F"))?;
+            writeln!(out)?;
+
+            let mut rows = vec![
+                TableRow::Cells(self.header(&["Project"])),
+                TableRow::Alignment,
+            ];
+            for p in day.leaves() {
+                rows.push(TableRow::Cells(self.cells(
+                    vec![escape_markdown(&p.project)],
+                    p.duration,
+                    p.rounded,
+                )));
+            }
+            let total = self.cells(
+                vec!["Total".to_string()],
+                day.total_duration,
+                day.rounded_duration,
+            );
+            rows.push(TableRow::Cells(
+                total.iter().map(|c| format!("**{}**", c)).collect(),
+            ));
+            write_table(out, &rows, 1)?;
+            writeln!(out)?;
+        }
+
+        write!(
+            out,
+            "**Total: {}**",
+            self.duration_format.format(&self.total_duration)
+        )?;
+        if let Some(rounded) = self.rounded_duration {
+            write!(out, " (rounded: {})", self.duration_format.format(&rounded))?;
+        }
+        writeln!(out)
+    }
+
+    /// The header of a table with the given label columns and the duration columns.
+    fn header(&self, labels: &[&str]) -> Vec<String> {
+        let mut header: Vec<String> = labels.iter().map(|l| l.to_string()).collect();
+        header.push("Duration".to_string());
+        if self.rounded_duration.is_some() {
+            header.push("Rounded".to_string());
+        }
+        header
+    }
+
+    /// The cells of a table row. The rounded column is only filled if rounding is active.
+    fn cells(
+        &self,
+        mut labels: Vec<String>,
+        exact: Duration,
+        rounded: Option<Duration>,
+    ) -> Vec<String> {
+        labels.push(self.duration_format.format(&exact));
+        if self.rounded_duration.is_some() {
+            labels.push(
+                rounded
+                    .map(|r| self.duration_format.format(&r))
+                    .unwrap_or_default(),
+            );
         }
+        labels
+    }
+}
+
+/// A row of a plain text table in Org or Markdown syntax.
+pub enum TableRow {
+    Cells(Vec<String>),
+    /// A horizontal rule as used by Org tables.
+    Rule,
+    /// The delimiter row of a Markdown table.
+    Alignment,
+}
+
+/// Writes an aligned table. The first `labels` columns are left aligned, all others contain
+/// durations and are right aligned.
+pub fn write_table<W: Write>(out: &mut W, rows: &[TableRow], labels: usize) -> io::Result<()> {
+    let mut widths: Vec<usize> = Vec::new();
+    for row in rows {
+        if let TableRow::Cells(cells) = row {
+            widths.resize(widths.len().max(cells.len()), 3);
+            for (w, c) in widths.iter_mut().zip(cells) {
+                *w = (*w).max(c.chars().count());
+            }
+        }
+    }
+
+    for row in rows {
+        let line: Vec<String> = match row {
+            TableRow::Cells(cells) => widths
+                .iter()
+                .enumerate()
+                .map(|(i, &w)| {
+                    let c = cells.get(i).map(String::as_str).unwrap_or_default();
+                    match i < labels {
+                        true => format!(" {:<w$} ", c, w = w),
+                        false => format!(" {:>w$} ", c, w = w),
+                    }
+                })
+                .collect(),
+            TableRow::Rule => widths.iter().map(|w| "-".repeat(w + 2)).collect(),
+            TableRow::Alignment => widths
+                .iter()
+                .enumerate()
+                .map(|(i, w)| match i < labels {
+                    true => format!(":{}", "-".repeat(w + 1)),
+                    false => format!("{}:", "-".repeat(w + 1)),
+                })
+                .collect(),
+        };
+        let separator = match row {
+            TableRow::Rule => "+",
+            _ => "|",
+        };
+        writeln!(out, "|{}|", line.join(separator))?;
     }
+    Ok(())
+}
+
+/// Org tables have no escaping, so `|` is replaced with the `\vert{}` entity.
+fn escape_org(text: &str) -> String {
+    text.replace('|', "\\vert{}")
+}
+
+pub fn escape_markdown(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('|', "\\|")
 }
 
 impl Display for DateRangeReport {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         self.days.iter().try_for_each(|i| i.fmt(f))?;
+        if let Some(rounded) = self.rounded_duration {
+            writeln!(
+                f,
+                "{} {:<40}[{}]{}",
+                ARROW.green(),
+                "Total".bold(),
+                self.duration_format.format(&rounded),
+                exact_suffix(self.total_duration, Some(rounded), self.duration_format)
+            )?;
+        }
         Ok(())
     }
 }
@@ -49,31 +290,80 @@ pub struct DayReport {
     pub date: NaiveDate,
     #[serde(serialize_with = "serialize_duration", rename = "total")]
     pub total_duration: Duration,
+    #[serde(
+        serialize_with = "serialize_opt_duration",
+        rename = "rounded_total",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub rounded_duration: Option<Duration>,
+    #[serde(
+        serialize_with = "serialize_opt_duration",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub target: Option<Duration>,
     pub projects: Vec<ProjectReport>,
+    #[serde(skip)]
+    pub duration_format: DurationFormat,
 }
 
 impl DayReport {
-    fn new(date: NaiveDate, projects: Vec<ProjectReport>) -> Self {
+    fn new(
+        date: NaiveDate,
+        projects: Vec<ProjectReport>,
+        rounding: Option<Rounding>,
+        duration_format: DurationFormat,
+    ) -> Self {
         Self {
             date,
             total_duration: projects
                 .iter()
                 .map(|p| p.duration)
                 .fold(Duration::zero(), |a, b| a.add(b)),
+            rounded_duration: rounding
+                .filter(|r| !r.applies_to(RoundingScope::Report))
+                .map(|_| {
+                    projects
+                        .iter()
+                        .map(|p| p.rounded.unwrap_or(p.duration))
+                        .fold(Duration::zero(), |a, b| a.add(b))
+                }),
+            target: None,
             projects,
+            duration_format,
         }
     }
 }
 
+impl DayReport {
+    /// The most detailed project reports, i.e. those without subprojects.
+    pub fn leaves(&self) -> impl Iterator<Item = &ProjectReport> {
+        self.projects.iter().flat_map(|p| p.leaves())
+    }
+
+    /// Finds the report of a project, which may be a subproject.
+    pub fn find(&self, project: &str) -> Option<&ProjectReport> {
+        self.projects.iter().find_map(|p| p.find(project))
+    }
+}
+
 impl Display for DayReport {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         writeln!(
             f,
-            "{} {}{:<25}[{}]",
+            "{} {}{:<25}[{}{}]{}",
             ARROW.green(),
             self.date.format("%a. %F"),
             ' ',
-            self.total_duration.to_pretty_string()
+            self.duration_format
+                .format(&self.rounded_duration.unwrap_or(self.total_duration)),
+            self.target
+                .map(|t| format!(" / {}", self.duration_format.format(&t)))
+                .unwrap_or_default(),
+            exact_suffix(
+                self.total_duration,
+                self.rounded_duration,
+                self.duration_format
+            )
         )?;
         self.projects.iter().try_for_each(|p| p.fmt(f))?;
         Ok(())
@@ -85,30 +375,315 @@ pub struct ProjectReport {
     pub project: String,
     #[serde(serialize_with = "serialize_duration")]
     pub duration: Duration,
+    #[serde(
+        serialize_with = "serialize_opt_duration",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub rounded: Option<Duration>,
+    /// The distinct notes of the entries, in the order they were tracked.
+    pub notes: Vec<String>,
+    /// The tags of all entries, sorted and without duplicates.
+    pub tags: Vec<String>,
+    /// The subprojects of hierarchical projects like `client/project`. The durations, notes and
+    /// tags of a project include those of its subprojects.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<ProjectReport>,
+    /// The commits authored during the tracked entries, if requested.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub commits: Option<Vec<Commit>>,
+    #[serde(skip)]
+    pub duration_format: DurationFormat,
+}
+
+impl ProjectReport {
+    /// Sums up the reports of subprojects.
+    fn group(project: String, children: Vec<ProjectReport>, format: DurationFormat) -> Self {
+        let sum = |f: fn(&ProjectReport) -> Duration| {
+            children
+                .iter()
+                .map(f)
+                .fold(Duration::zero(), |a, b| a.add(b))
+        };
+        let mut notes: Vec<String> = Vec::new();
+        for note in children.iter().flat_map(|c| &c.notes) {
+            if !notes.contains(note) {
+                notes.push(note.clone());
+            }
+        }
+        let tags: BTreeSet<&String> = children.iter().flat_map(|c| &c.tags).collect();
+        let commits = children.iter().any(|c| c.commits.is_some()).then(|| {
+            let mut commits: Vec<Commit> = Vec::new();
+            for commit in children.iter().flat_map(|c| c.commits.iter().flatten()) {
+                if !commits.iter().any(|c| c.hash == commit.hash) {
+                    commits.push(commit.clone());
+                }
+            }
+            commits.sort_by_key(|c| c.time);
+            commits
+        });
+        ProjectReport {
+            project,
+            duration: sum(|c| c.duration),
+            rounded: children
+                .iter()
+                .any(|c| c.rounded.is_some())
+                .then(|| sum(|c| c.rounded.unwrap_or(c.duration))),
+            notes,
+            tags: tags.into_iter().cloned().collect(),
+            children,
+            commits,
+            duration_format: format,
+        }
+    }
+
+    /// Removes the subprojects below the given number of levels.
+    fn collapse(&mut self, depth: u32) {
+        match depth {
+            0 | 1 => self.children.clear(),
+            _ => self.children.iter_mut().for_each(|c| c.collapse(depth - 1)),
+        }
+    }
+
+    /// This project if it has no subprojects, all subprojects without subprojects otherwise.
+    pub fn leaves(&self) -> Box<dyn Iterator<Item = &ProjectReport> + '_> {
+        match self.children.is_empty() {
+            true => Box::new(std::iter::once(self)),
+            false => Box::new(self.children.iter().flat_map(|c| c.leaves())),
+        }
+    }
+
+    /// Finds the report of the given project among this project and its subprojects.
+    pub fn find(&self, project: &str) -> Option<&ProjectReport> {
+        match self.project == project {
+            true => Some(self),
+            false => self.children.iter().find_map(|c| c.find(project)),
+        }
+    }
+
+    fn fmt_level(&self, f: &mut Formatter<'_>, level: usize) -> std::fmt::Result {
+        writeln!(
+            f,
+            "    {}{} {:<width$} [{}]{}",
+            "    ".repeat(level),
+            ARROW,
+            base_name(&self.project).bold(),
+            self.duration_format
+                .format(&self.rounded.unwrap_or(self.duration)),
+            exact_suffix(self.duration, self.rounded, self.duration_format),
+            width = 35usize.saturating_sub(4 * level),
+        )?;
+        if self.children.is_empty() {
+            for commit in self.commits.iter().flatten() {
+                writeln!(
+                    f,
+                    "        {}{} {}",
+                    "    ".repeat(level),
+                    commit.short_hash().yellow(),
+                    commit.subject
+                )?;
+            }
+        }
+        self.children
+            .iter()
+            .try_for_each(|c| c.fmt_level(f, level + 1))
+    }
 }
 
 impl Display for ProjectReport {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.fmt_level(f, 0)
+    }
+}
+
+/// The time tracked per project over a whole report, together with the project budgets.
+#[derive(Serialize)]
+pub struct SummaryReport {
+    pub range: Range<NaiveDate>,
+    #[serde(serialize_with = "serialize_duration")]
+    pub total: Duration,
+    pub projects: Vec<ProjectSummary>,
+    #[serde(skip)]
+    pub duration_format: DurationFormat,
+}
+
+#[derive(Serialize)]
+pub struct ProjectSummary {
+    pub project: String,
+    #[serde(serialize_with = "serialize_duration")]
+    pub duration: Duration,
+    /// The budget status in the period containing the last day of the report.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub budget: Option<BudgetStatus>,
+}
+
+impl Display for SummaryReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for p in &self.projects {
+            let budget = match &p.budget {
+                Some(b) if b.remaining < Duration::zero() => {
+                    b.describe(self.duration_format).red().to_string()
+                }
+                Some(b) => b.describe(self.duration_format),
+                None => String::new(),
+            };
+            writeln!(
+                f,
+                "{} {:<40}[{}]  {}",
+                ARROW.green(),
+                p.project.as_str().bold(),
+                self.duration_format.format(&p.duration),
+                budget
+            )?;
+        }
         writeln!(
             f,
-            "    {} {:<35} [{}]",
-            ARROW,
-            self.project.as_str().bold(),
-            self.duration.to_pretty_string(),
+            "{} {:<40}[{}]",
+            ARROW.green(),
+            "Total".bold(),
+            self.duration_format.format(&self.total)
         )
     }
 }
 
-fn serialize_duration<S>(d: &Duration, serializer: S) -> Result<S::Ok, S::Error>
+/// Shows the exact duration next to a rounded one, so that rounding never hides tracked time.
+fn exact_suffix(exact: Duration, rounded: Option<Duration>, format: DurationFormat) -> String {
+    match rounded {
+        Some(r) if r != exact => format!(" ({})", format.format(&exact)).dimmed().to_string(),
+        _ => String::new(),
+    }
+}
+
+/// The overtime balance over a range of days, grouped by week.
+#[derive(Serialize)]
+pub struct BalanceReport {
+    /// The first day of the balance.
+    pub from: NaiveDate,
+    /// The last day of the balance, which is included.
+    pub to: NaiveDate,
+    #[serde(serialize_with = "serialize_duration")]
+    pub tracked: Duration,
+    #[serde(serialize_with = "serialize_duration")]
+    pub target: Duration,
+    #[serde(serialize_with = "serialize_duration")]
+    pub balance: Duration,
+    pub weeks: Vec<WeekBalance>,
+    #[serde(skip)]
+    pub duration_format: DurationFormat,
+}
+
+#[derive(Serialize)]
+pub struct WeekBalance {
+    pub week: String,
+    pub start: NaiveDate,
+    #[serde(serialize_with = "serialize_duration")]
+    pub tracked: Duration,
+    #[serde(serialize_with = "serialize_duration")]
+    pub target: Duration,
+    /// The running balance at the end of this week.
+    #[serde(serialize_with = "serialize_duration")]
+    pub balance: Duration,
+}
+
+impl Display for BalanceReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let format = |d: &Duration| self.duration_format.format(d);
+        for w in &self.weeks {
+            writeln!(
+                f,
+                "{} {} ({}){:<19}[{} / {}]  {}",
+                ARROW.green(),
+                w.week,
+                w.start.format("%F"),
+                ' ',
+                format(&w.tracked),
+                format(&w.target),
+                signed(format(&w.balance), w.balance)
+            )?;
+        }
+        writeln!(
+            f,
+            "{} {:<40}[{} / {}]  {}",
+            ARROW.green(),
+            "Balance".bold(),
+            format(&self.tracked),
+            format(&self.target),
+            signed(format(&self.balance), self.balance).bold()
+        )
+    }
+}
+
+fn signed(formatted: String, d: Duration) -> colored::ColoredString {
+    match d {
+        d if d < Duration::zero() => formatted.red(),
+        d if d > Duration::zero() => format!("+{}", formatted).green(),
+        _ => formatted.normal(),
+    }
+}
+
+pub fn serialize_duration<S>(d: &Duration, serializer: S) -> Result<S::Ok, S::Error>
 where
     S: Serializer,
 {
     serializer.serialize_i64(d.num_minutes())
 }
 
+fn serialize_opt_duration<S>(d: &Option<Duration>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    match d {
+        Some(d) => serialize_duration(d, serializer),
+        None => serializer.serialize_none(),
+    }
+}
+
 impl ReportCreator<'_> {
     pub fn new(time_log: &TimeLog) -> ReportCreator {
-        ReportCreator { time_log }
+        ReportCreator {
+            time_log,
+            rounding: None,
+            duration_format: DurationFormat::default(),
+            targets: None,
+            depth: None,
+            project: None,
+            repositories: None,
+        }
+    }
+
+    /// Adds the daily targets to all reports created afterwards.
+    pub fn with_targets(mut self, targets: Option<Targets>) -> Self {
+        self.targets = targets;
+        self
+    }
+
+    /// Applies the given rounding policy to all reports created afterwards.
+    pub fn with_rounding(mut self, rounding: Option<Rounding>) -> Self {
+        self.rounding = rounding;
+        self
+    }
+
+    /// Collapses hierarchical projects to the given number of levels.
+    pub fn with_depth(mut self, depth: Option<u32>) -> Self {
+        self.depth = depth;
+        self
+    }
+
+    /// Only includes the projects selected by the pattern.
+    pub fn with_project(mut self, project: Option<ProjectPattern>) -> Self {
+        self.project = project;
+        self
+    }
+
+    /// Lists the commits of the given repositories that were authored during the tracked entries.
+    pub fn with_commits(mut self, repositories: Option<Vec<RepositoryLog>>) -> Self {
+        self.repositories = repositories;
+        self
+    }
+
+    /// Sets the format in which durations are displayed.
+    pub fn with_duration_format(mut self, duration_format: DurationFormat) -> Self {
+        self.duration_format = duration_format;
+        self
     }
 
     pub fn report_days(
@@ -122,31 +697,196 @@ impl ReportCreator<'_> {
         let mut child_reports: Vec<DayReport> = Vec::new();
         let mut curr_date: Date<Local> = start_date;
         while curr_date <= date {
-            if !self.time_log.for_day(curr_date).is_empty() || include_empty_days {
-                child_reports.push(self.report_day(curr_date));
+            let report = self.report_day(curr_date);
+            if !report.projects.is_empty() || include_empty_days {
+                child_reports.push(report);
             }
             curr_date = curr_date.succ();
         }
 
-        DateRangeReport::new(start_date..date, child_reports)
+        DateRangeReport::new(
+            start_date..date,
+            child_reports,
+            self.rounding,
+            self.duration_format,
+        )
     }
 
     pub fn report_day(&self, date: Date<Local>) -> DayReport {
-        let log = self.time_log.for_day(date);
+        self.report_date(date.naive_local())
+    }
+
+    pub fn report_date(&self, date: NaiveDate) -> DayReport {
+        let log = self
+            .time_log
+            .entries(date..=date)
+            .map(|(_, e)| e)
+            .filter(|e| {
+                self.project
+                    .as_ref()
+                    .is_none_or(|p| p.matches(&e.project_name))
+            });
 
         let groups = Self::group_by_key(log, |i| String::from(&i.project_name));
-        let mut projects: Vec<ProjectReport> = groups.iter().map(Self::report_project).collect();
-        projects.sort_unstable_by(|a, b| a.project.cmp(&b.project));
+        let mut projects = self.nest(groups.iter().map(|g| self.report_project(g)).collect(), 0);
+        if let Some(depth) = self.depth {
+            projects.iter_mut().for_each(|p| p.collapse(depth));
+        }
 
-        DayReport::new(date.naive_local(), projects)
+        let mut report = DayReport::new(date, projects, self.rounding, self.duration_format);
+        report.target = self
+            .targets
+            .map(|t| t.for_date(date, &self.time_log.calendar));
+        report
+    }
+
+    /// Sums up the (rounded) durations of each project in the report.
+    pub fn report_summary(&self, report: &DateRangeReport) -> SummaryReport {
+        let mut durations: BTreeMap<&str, Duration> = BTreeMap::new();
+        for p in report.days.iter().flat_map(|d| d.leaves()) {
+            let d = durations.entry(&p.project).or_insert_with(Duration::zero);
+            *d = *d + p.rounded.unwrap_or(p.duration);
+        }
+        SummaryReport {
+            range: report.range.clone(),
+            total: report.rounded_duration.unwrap_or(report.total_duration),
+            projects: durations
+                .into_iter()
+                .map(|(project, duration)| ProjectSummary {
+                    project: project.to_string(),
+                    duration,
+                    budget: self.time_log.budget_status(project, report.range.end),
+                })
+                .collect(),
+            duration_format: self.duration_format,
+        }
     }
 
-    fn report_project(tuple: (&String, &Vec<&LogEntry>)) -> ProjectReport {
+    /// Compares the tracked time to the targets for every day in `days`.
+    pub fn report_balance(
+        &self,
+        days: RangeInclusive<NaiveDate>,
+        targets: &Targets,
+    ) -> BalanceReport {
+        let mut weeks: Vec<WeekBalance> = Vec::new();
+        let mut balance = Duration::zero();
+        let mut date = *days.start();
+        while date <= *days.end() {
+            let tracked = self.time_log.tracked_time(date..=date);
+            let target = targets.for_date(date, &self.time_log.calendar);
+            balance = balance + tracked - target;
+
+            let iso = date.iso_week();
+            let week = format!("{}-W{:02}", iso.year(), iso.week());
+            match weeks.last_mut() {
+                Some(w) if w.week == week => {
+                    w.tracked = w.tracked + tracked;
+                    w.target = w.target + target;
+                    w.balance = balance;
+                }
+                _ => weeks.push(WeekBalance {
+                    week,
+                    start: date,
+                    tracked,
+                    target,
+                    balance,
+                }),
+            }
+            date = date.succ_opt().unwrap();
+        }
+
+        let sum = |f: fn(&WeekBalance) -> Duration| {
+            weeks.iter().map(f).fold(Duration::zero(), |a, b| a.add(b))
+        };
+        BalanceReport {
+            from: *days.start(),
+            to: *days.end(),
+            tracked: sum(|w| w.tracked),
+            target: sum(|w| w.target),
+            balance,
+            weeks,
+            duration_format: self.duration_format,
+        }
+    }
+
+    fn report_project(&self, tuple: (&String, &Vec<&LogEntry>)) -> ProjectReport {
         let (name, entries) = tuple;
+        let duration = Self::sum_time(entries);
+        let rounded = self.rounding.and_then(|r| match r.scope {
+            RoundingScope::Entry => Some(
+                entries
+                    .iter()
+                    .fold(Duration::zero(), |d, e| d.add(r.round(e.to_duration()))),
+            ),
+            RoundingScope::Day => Some(r.round(duration)),
+            RoundingScope::Report => None,
+        });
+        let mut notes: Vec<String> = Vec::new();
+        for note in entries.iter().filter_map(|e| e.note.as_ref()) {
+            if !notes.contains(note) {
+                notes.push(note.clone());
+            }
+        }
+        let tags: BTreeSet<&String> = entries.iter().flat_map(|e| &e.tags).collect();
         ProjectReport {
-            duration: Self::sum_time(entries),
+            duration,
+            rounded,
+            notes,
+            tags: tags.into_iter().cloned().collect(),
             project: name.to_string(),
+            children: Vec::new(),
+            commits: self.commits_during(entries),
+            duration_format: self.duration_format,
+        }
+    }
+
+    /// The commits authored during the entries. Repositories linked to a project only count for
+    /// that project and its subprojects, all others for the projects without linked repository.
+    fn commits_during(&self, entries: &[&LogEntry]) -> Option<Vec<Commit>> {
+        let repositories = self.repositories.as_ref()?;
+        let mut commits: Vec<Commit> = Vec::new();
+        for entry in entries {
+            let linked = |r: &RepositoryLog| {
+                r.project
+                    .as_ref()
+                    .is_some_and(|p| is_within(&entry.project_name, p))
+            };
+            let has_linked = repositories.iter().any(linked);
+            let during = repositories
+                .iter()
+                .filter(|r| linked(r) || (r.project.is_none() && !has_linked))
+                .flat_map(|r| &r.commits)
+                .filter(|c| entry.start <= c.time && c.time <= entry.end);
+            for commit in during {
+                if !commits.iter().any(|c| c.hash == commit.hash) {
+                    commits.push(commit.clone());
+                }
+            }
+        }
+        commits.sort_by_key(|c| c.time);
+        Some(commits)
+    }
+
+    /// Groups the reports of hierarchical projects below a report per level, starting at `level`.
+    fn nest(&self, projects: Vec<ProjectReport>, level: usize) -> Vec<ProjectReport> {
+        // Both separators are equivalent, so `client:web` and `client/web` end up in one group.
+        let normalize = |name: &str| name.split(SEPARATORS).collect::<Vec<_>>().join("/");
+        let mut groups: BTreeMap<String, Vec<ProjectReport>> = BTreeMap::new();
+        for p in projects {
+            groups
+                .entry(normalize(ancestor(&p.project, level + 1)))
+                .or_default()
+                .push(p);
         }
+        groups
+            .into_iter()
+            .map(|(name, mut members)| match members.len() {
+                1 if normalize(&members[0].project) == name => members.pop().unwrap(),
+                _ => {
+                    ProjectReport::group(name, self.nest(members, level + 1), self.duration_format)
+                }
+            })
+            .collect()
     }
 
     fn sum_time(vec: &[&LogEntry]) -> Duration {
@@ -154,13 +894,13 @@ impl ReportCreator<'_> {
             .fold(Duration::zero(), |d, e| d.add(e.to_duration()))
     }
 
-    fn group_by_key<K: Eq + Hash>(
-        vec: &[LogEntry],
+    fn group_by_key<'a, K: Eq + Hash>(
+        entries: impl Iterator<Item = &'a LogEntry>,
         key_extractor: fn(&LogEntry) -> K,
-    ) -> GroupBy<'_, K> {
+    ) -> GroupBy<'a, K> {
         let mut result: HashMap<K, Vec<&LogEntry>> = HashMap::new();
 
-        for entry in vec.iter() {
+        for entry in entries {
             let key = key_extractor(entry);
             let v = result.entry(key).or_insert_with(Vec::<&LogEntry>::new);
             v.push(entry);
@@ -173,7 +913,8 @@ impl ReportCreator<'_> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use chrono::{Datelike, TimeZone};
+    use crate::calendar::{DayOff, WeeklyTargets};
+    use chrono::TimeZone;
     use std::collections::BTreeMap;
     use std::iter::FromIterator;
 
@@ -264,6 +1005,301 @@ mod tests {
         assert!(r_string.contains("[00h 10m]"));
     }
 
+    fn tl_hierarchy(today: Date<Local>) -> TimeLog {
+        TimeLog::new_testing_only(BTreeMap::from_iter(vec![(
+            today.naive_local(),
+            vec![
+                create_log(1, 30, "acme/web/frontend"),
+                create_log(1, 10, "acme:web/backend"),
+                create_log(1, 5, "acme"),
+                create_log(1, 20, "other"),
+            ],
+        )]))
+    }
+
+    #[test]
+    fn test_project_hierarchy() {
+        let today = test_date().with_day(1).unwrap();
+        let tl = tl_hierarchy(today);
+        let report = ReportCreator::new(&tl).report_day(today);
+
+        assert_eq!(report.total_duration, Duration::minutes(65));
+        let names = |p: &[ProjectReport]| p.iter().map(|c| c.project.clone()).collect::<Vec<_>>();
+        assert_eq!(names(&report.projects), ["acme", "other"]);
+        let acme = &report.projects[0];
+        assert_eq!(acme.duration, Duration::minutes(45));
+        assert_eq!(names(&acme.children), ["acme", "acme/web"]);
+        assert_eq!(
+            names(&report.find("acme/web").unwrap().children),
+            ["acme:web/backend", "acme/web/frontend"]
+        );
+        assert_eq!(report.leaves().count(), 4);
+
+        let text = report.to_string();
+        assert!(text.contains("        ❯ frontend"));
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(
+            json["projects"][0]["children"][1]["children"][1]["duration"],
+            30
+        );
+        assert!(json["projects"][1].get("children").is_none());
+    }
+
+    #[test]
+    fn test_project_depth_and_filter() {
+        let today = test_date().with_day(1).unwrap();
+        let tl = tl_hierarchy(today);
+
+        let report = ReportCreator::new(&tl)
+            .with_depth(Some(1))
+            .report_day(today);
+        assert!(report.projects.iter().all(|p| p.children.is_empty()));
+        assert_eq!(report.projects[0].duration, Duration::minutes(45));
+
+        let report = ReportCreator::new(&tl)
+            .with_project(Some("acme/web/*".parse().unwrap()))
+            .report_day(today);
+        assert_eq!(report.total_duration, Duration::minutes(40));
+
+        let report = ReportCreator::new(&tl)
+            .with_project(Some("acme/*".parse().unwrap()))
+            .with_rounding(Some("15m:up:day".parse().unwrap()))
+            .report_day(today);
+        assert_eq!(report.total_duration, Duration::minutes(45));
+        assert_eq!(report.rounded_duration, Some(Duration::minutes(60)));
+    }
+
+    #[test]
+    fn test_commits() {
+        let today = test_date().with_day(1).unwrap();
+        let tl = tl_hierarchy(today);
+        let commit = |hash: &str, min: u32| Commit {
+            hash: hash.repeat(8),
+            time: today.and_hms(4, min, 0),
+            subject: format!("Commit {}", hash),
+        };
+        let repositories = vec![
+            RepositoryLog {
+                project: Some("acme/web".to_string()),
+                commits: vec![commit("a", 8)],
+            },
+            RepositoryLog {
+                project: None,
+                commits: vec![commit("b", 15), commit("c", 59)],
+            },
+        ];
+        let report = ReportCreator::new(&tl)
+            .with_commits(Some(repositories))
+            .report_day(today);
+
+        let hashes = |project: &str| {
+            let commits = report.find(project).unwrap().commits.as_ref().unwrap();
+            commits.iter().map(|c| c.short_hash()).collect::<Vec<_>>()
+        };
+        assert_eq!(hashes("acme/web/frontend"), ["aaaaaaa"]);
+        assert_eq!(hashes("acme/web"), ["aaaaaaa"]);
+        assert_eq!(hashes("acme"), ["aaaaaaa"]);
+        let own_time = &report.projects[0].children[0];
+        assert_eq!(own_time.commits.as_deref(), Some(&[][..]));
+        assert_eq!(hashes("other"), ["bbbbbbb"]);
+
+        let text = report.to_string();
+        assert!(text.contains("bbbbbbb Commit b"));
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["projects"][1]["commits"][0]["subject"], "Commit b");
+
+        let report = ReportCreator::new(&tl).report_day(today);
+        assert!(serde_json::to_value(&report).unwrap()["projects"][1]
+            .get("commits")
+            .is_none());
+    }
+
+    #[test]
+    fn test_rounding_per_entry() {
+        let today = test_date().with_day(1).unwrap();
+        let tl = TimeLog::new_testing_only(BTreeMap::from_iter(vec![(
+            today.naive_local(),
+            vec![create_log(1, 10, "Foo"), create_log(1, 10, "Foo")],
+        )]));
+
+        let rc = ReportCreator::new(&tl).with_rounding(Some("15m:up:entry".parse().unwrap()));
+        let report = rc.report_days(today, 1, false);
+
+        assert_eq!(report.total_duration, Duration::minutes(20));
+        assert_eq!(report.rounded_duration, Some(Duration::minutes(30)));
+        assert_eq!(
+            report.days[0].projects[0].rounded,
+            Some(Duration::minutes(30))
+        );
+    }
+
+    #[test]
+    fn test_rounding_per_day() {
+        let today = test_date().with_day(1).unwrap();
+        let tl = TimeLog::new_testing_only(BTreeMap::from_iter(vec![(
+            today.naive_local(),
+            vec![create_log(1, 10, "Foo"), create_log(1, 10, "Foo")],
+        )]));
+
+        let rc = ReportCreator::new(&tl).with_rounding(Some("15m:up:day".parse().unwrap()));
+        let report = rc.report_days(today, 1, false);
+
+        assert_eq!(report.days[0].rounded_duration, Some(Duration::minutes(30)));
+        assert_eq!(
+            report.days[0].projects[0].rounded,
+            Some(Duration::minutes(30))
+        );
+    }
+
+    #[test]
+    fn test_rounding_per_report() {
+        let today = test_date().with_day(1).unwrap();
+        let tomorrow = test_date().with_day(2).unwrap();
+        let tl = tl_multiple_days(today, tomorrow);
+
+        let rc = ReportCreator::new(&tl).with_rounding(Some("15m:nearest:report".parse().unwrap()));
+        let report = rc.report_days(tomorrow, 2, true);
+
+        assert_eq!(report.total_duration, Duration::minutes(50));
+        assert_eq!(report.rounded_duration, Some(Duration::minutes(45)));
+        assert!(report.days.iter().all(|d| d.rounded_duration.is_none()));
+
+        let json = serde_json::to_string(&report).unwrap();
+        assert!(json.contains("\"total\":50"));
+        assert!(json.contains("\"rounded_total\":45"));
+    }
+
+    #[test]
+    fn test_display_duration_format() {
+        let today = test_date().with_day(1).unwrap();
+        let tomorrow = test_date().with_day(2).unwrap();
+        let tl = tl_multiple_days(today, tomorrow);
+        let rc = ReportCreator::new(&tl).with_duration_format(DurationFormat::Decimal);
+
+        let r_string = rc.report_days(tomorrow, 2, true).to_string();
+
+        assert!(r_string.contains("[0.50]"));
+        assert!(r_string.contains("[0.67]"));
+    }
+
+    #[test]
+    fn test_day_targets() {
+        let today = test_date().with_day(3).unwrap();
+        let mut tl = TimeLog::new_testing_only(BTreeMap::from_iter(vec![(
+            today.naive_local(),
+            vec![create_log(3, 30, "Foo")],
+        )]));
+        tl.calendar
+            .mark(test_date().with_day(4).unwrap().naive_local(), DayOff::Sick);
+        let targets = Targets::new(&WeeklyTargets::default(), Some(Duration::hours(8)));
+
+        let rc = ReportCreator::new(&tl).with_targets(targets);
+
+        // 2000-01-03 is a Monday, 2000-01-04 a sick day.
+        assert_eq!(rc.report_day(today).target, Some(Duration::hours(8)));
+        let sick_day = test_date().with_day(4).unwrap();
+        assert_eq!(rc.report_day(sick_day).target, Some(Duration::zero()));
+        assert!(rc
+            .report_day(today)
+            .to_string()
+            .contains("[00h 30m / 08h 00m]"));
+    }
+
+    #[test]
+    fn test_balance() {
+        let monday = test_date().with_day(3).unwrap();
+        let tl = TimeLog::new_testing_only(BTreeMap::from_iter(vec![
+            (monday.naive_local(), vec![create_log(3, 30, "Foo")]),
+            (
+                test_date().with_day(10).unwrap().naive_local(),
+                vec![create_log(10, 50, "Foo")],
+            ),
+        ]));
+        let targets = Targets::new(&WeeklyTargets::default(), Some(Duration::minutes(10))).unwrap();
+
+        let rc = ReportCreator::new(&tl);
+        let start = test_date().with_day(2).unwrap().naive_local();
+        let end = test_date().with_day(10).unwrap().naive_local();
+        let report = rc.report_balance(start..=end, &targets);
+
+        // Sunday 2000-01-02 belongs to the 52nd week of 1999.
+        assert_eq!(report.weeks.len(), 3);
+        assert_eq!(report.weeks[1].week, "2000-W01");
+        assert_eq!(report.weeks[1].target, Duration::minutes(50));
+        assert_eq!(report.weeks[1].balance, Duration::minutes(-20));
+        assert_eq!(report.target, Duration::minutes(60));
+        assert_eq!(report.balance, Duration::minutes(20));
+    }
+
+    #[test]
+    fn test_csv() {
+        let today = test_date().with_day(1).unwrap();
+        let tomorrow = test_date().with_day(2).unwrap();
+        let tl = tl_multiple_days(today, tomorrow);
+        let report = ReportCreator::new(&tl)
+            .with_rounding(Some("15m:up".parse().unwrap()))
+            .report_days(tomorrow, 2, true);
+
+        let mut out = Vec::new();
+        report
+            .write_csv(&mut CsvWriter::new(&mut out, ','), true)
+            .unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "date,project,duration,rounded\r\n\
+             2000-01-01,Bar,10,15\r\n\
+             2000-01-01,Foo,30,30\r\n\
+             2000-01-02,Bar,10,15\r\n"
+        );
+    }
+
+    fn golden_report(rounding: Option<&str>) -> DateRangeReport {
+        let today = test_date().with_day(1).unwrap();
+        let tomorrow = test_date().with_day(2).unwrap();
+        let mut tl = tl_multiple_days(today, tomorrow);
+        tl.add_entry(create_log(2, 45, "Foo | Bar"));
+        ReportCreator::new(&tl)
+            .with_rounding(rounding.map(|r| r.parse().unwrap()))
+            .report_days(tomorrow, 2, true)
+    }
+
+    fn render(
+        report: &DateRangeReport,
+        f: fn(&DateRangeReport, &mut Vec<u8>) -> io::Result<()>,
+    ) -> String {
+        let mut out = Vec::new();
+        f(report, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_org() {
+        let org = render(&golden_report(None), DateRangeReport::write_org);
+        assert_eq!(org, include_str!("../tests/fixtures/report.org"));
+    }
+
+    #[test]
+    fn test_org_rounded() {
+        let org = render(&golden_report(Some("15m:up")), DateRangeReport::write_org);
+        assert_eq!(org, include_str!("../tests/fixtures/report-rounded.org"));
+    }
+
+    #[test]
+    fn test_markdown() {
+        let md = render(&golden_report(None), DateRangeReport::write_markdown);
+        assert_eq!(md, include_str!("../tests/fixtures/report.md"));
+    }
+
+    #[test]
+    fn test_markdown_rounded() {
+        let md = render(
+            &golden_report(Some("15m:up")),
+            DateRangeReport::write_markdown,
+        );
+        assert_eq!(md, include_str!("../tests/fixtures/report-rounded.md"));
+    }
+
     fn tl_multiple_days(today: Date<Local>, tomorrow: Date<Local>) -> TimeLog {
         TimeLog::new_testing_only(BTreeMap::from_iter(vec![
             (
@@ -279,6 +1315,8 @@ mod tests {
             start: test_date().with_day(day).unwrap().and_hms(4, 0, 20),
             end: test_date().with_day(day).unwrap().and_hms(4, dur, 20),
             project_name: name.to_string(),
+            note: None,
+            tags: Vec::new(),
         }
     }
 
@@ -0,0 +1,161 @@
+use crate::duration::parse_duration;
+use crate::TrackieError;
+use chrono::Duration;
+use serde::Deserialize;
+use std::str::FromStr;
+
+/// The direction in which a duration gets rounded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RoundingMode {
+    Up,
+    Down,
+    Nearest,
+}
+
+/// The level at which rounding is applied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RoundingScope {
+    /// Every single log entry is rounded before it is summed up.
+    Entry,
+    /// The sum of a project on a single day is rounded.
+    Day,
+    /// Only the total of the whole report is rounded.
+    Report,
+}
+
+/// A rounding policy as used for billing, e.g. `15m:up`.
+///
+/// The textual representation is `<granularity>:<mode>[:<scope>]`, where the scope
+/// defaults to [RoundingScope::Entry].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct Rounding {
+    pub granularity: Duration,
+    pub mode: RoundingMode,
+    pub scope: RoundingScope,
+}
+
+impl Rounding {
+    pub fn round(&self, d: Duration) -> Duration {
+        let step = self.granularity.num_seconds();
+        let secs = d.num_seconds();
+        let steps = match self.mode {
+            RoundingMode::Down => secs.div_euclid(step),
+            RoundingMode::Up => (secs + step - 1).div_euclid(step),
+            RoundingMode::Nearest => (secs + step / 2).div_euclid(step),
+        };
+        Duration::seconds(steps * step)
+    }
+
+    pub fn applies_to(&self, scope: RoundingScope) -> bool {
+        self.scope == scope
+    }
+}
+
+impl FromStr for RoundingMode {
+    type Err = TrackieError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "up" => Ok(RoundingMode::Up),
+            "down" => Ok(RoundingMode::Down),
+            "nearest" => Ok(RoundingMode::Nearest),
+            _ => Err(TrackieError::new(
+                format!("Unknown rounding mode '{}'. Use up, down or nearest.", s).as_str(),
+            )),
+        }
+    }
+}
+
+impl FromStr for RoundingScope {
+    type Err = TrackieError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "entry" => Ok(RoundingScope::Entry),
+            "day" => Ok(RoundingScope::Day),
+            "report" => Ok(RoundingScope::Report),
+            _ => Err(TrackieError::new(
+                format!("Unknown rounding scope '{}'. Use entry, day or report.", s).as_str(),
+            )),
+        }
+    }
+}
+
+impl FromStr for Rounding {
+    type Err = TrackieError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split(':');
+        let granularity = parse_duration(parts.next().unwrap_or_default())?;
+        if granularity.num_seconds() <= 0 {
+            return Err(TrackieError::new("Rounding granularity must be positive."));
+        }
+        let mode = parts.next().unwrap_or("nearest").parse()?;
+        let scope = parts.next().unwrap_or("entry").parse()?;
+        if parts.next().is_some() {
+            return Err(TrackieError::new(
+                format!(
+                    "Invalid rounding '{}'. Expected <granularity>:<mode>[:<scope>].",
+                    s
+                )
+                .as_str(),
+            ));
+        }
+        Ok(Rounding {
+            granularity,
+            mode,
+            scope,
+        })
+    }
+}
+
+impl TryFrom<String> for Rounding {
+    type Error = TrackieError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_rounding() {
+        let r: Rounding = "15m:up".parse().unwrap();
+        assert_eq!(r.granularity, Duration::minutes(15));
+        assert_eq!(r.mode, RoundingMode::Up);
+        assert_eq!(r.scope, RoundingScope::Entry);
+
+        let r: Rounding = "6m:nearest:report".parse().unwrap();
+        assert_eq!(r.scope, RoundingScope::Report);
+    }
+
+    #[test]
+    fn parse_invalid_rounding() {
+        assert!("0m:up".parse::<Rounding>().is_err());
+        assert!("15m:sideways".parse::<Rounding>().is_err());
+        assert!("15m:up:week".parse::<Rounding>().is_err());
+        assert!("15m:up:day:extra".parse::<Rounding>().is_err());
+    }
+
+    #[test]
+    fn round_modes() {
+        let d = Duration::minutes(22);
+        let round = |s: &str| s.parse::<Rounding>().unwrap().round(d);
+
+        assert_eq!(round("15m:up"), Duration::minutes(30));
+        assert_eq!(round("15m:down"), Duration::minutes(15));
+        assert_eq!(round("15m:nearest"), Duration::minutes(15));
+        assert_eq!(round("6m:nearest"), Duration::minutes(24));
+    }
+
+    #[test]
+    fn round_exact_multiple_is_unchanged() {
+        let r: Rounding = "15m:up".parse().unwrap();
+        assert_eq!(r.round(Duration::minutes(45)), Duration::minutes(45));
+        assert_eq!(r.round(Duration::zero()), Duration::zero());
+    }
+}
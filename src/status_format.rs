@@ -0,0 +1,384 @@
+use crate::pretty_string::DurationFormat;
+use crate::time_log::PendingLog;
+use crate::TrackieError;
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Duration, Local};
+use clap::ValueEnum;
+use serde::Serialize;
+use std::iter::Peekable;
+use std::str::Chars;
+
+const PLACEHOLDERS: &str = "pdtDsTWngRB";
+/// The widest a placeholder may be padded, so that a typo cannot exhaust the memory.
+const MAX_WIDTH: usize = 1000;
+
+/// A parsed format string of the `status` command.
+///
+/// Placeholders have the form `%[-][0][width]<name>[{argument}]`, e.g. `%-20p` or `%D{decimal}`.
+/// A literal `%` is written as `%%`.
+#[derive(Debug, PartialEq)]
+pub struct StatusFormat {
+    tokens: Vec<Token>,
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Literal(String),
+    Placeholder(Placeholder),
+}
+
+#[derive(Debug, PartialEq, Default)]
+struct Placeholder {
+    name: char,
+    argument: Option<String>,
+    width: usize,
+    left_align: bool,
+    zero_pad: bool,
+}
+
+/// Everything a [StatusFormat] can refer to.
+pub struct StatusContext<'a> {
+    pub pending: &'a PendingLog,
+    pub duration: Duration,
+    pub today: Duration,
+    pub week: Duration,
+    pub daily_target: Option<Duration>,
+    pub budget_remaining: Option<Duration>,
+    pub duration_format: DurationFormat,
+}
+
+/// The machine-readable output of `status --json`.
+///
+/// Only `tracking` is present if no time is currently tracked.
+#[derive(Debug, Serialize)]
+pub struct StatusJson<'a> {
+    pub tracking: bool,
+    #[serde(flatten, skip_serializing_if = "Option::is_none")]
+    pub pending: Option<PendingStatusJson<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PendingStatusJson<'a> {
+    pub project: &'a str,
+    pub start: DateTime<Local>,
+    pub elapsed_seconds: i64,
+    pub note: Option<&'a str>,
+    pub tags: &'a [String],
+    pub today_seconds: i64,
+}
+
+impl StatusJson<'_> {
+    pub fn idle() -> Self {
+        StatusJson {
+            tracking: false,
+            pending: None,
+        }
+    }
+}
+
+impl<'a> From<&StatusContext<'a>> for StatusJson<'a> {
+    fn from(ctx: &StatusContext<'a>) -> Self {
+        StatusJson {
+            tracking: true,
+            pending: Some(PendingStatusJson {
+                project: &ctx.pending.project_name,
+                start: ctx.pending.start,
+                elapsed_seconds: ctx.duration.num_seconds(),
+                note: ctx.pending.note.as_deref(),
+                tags: &ctx.pending.tags,
+                today_seconds: ctx.today.num_seconds(),
+            }),
+        }
+    }
+}
+
+impl StatusFormat {
+    pub fn parse(format: &str) -> Result<StatusFormat, TrackieError> {
+        let mut tokens = Vec::new();
+        let mut literal = String::new();
+        let mut chars = format.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                literal.push(c);
+            } else if chars.peek() == Some(&'%') {
+                chars.next();
+                literal.push('%');
+            } else {
+                if !literal.is_empty() {
+                    tokens.push(Token::Literal(std::mem::take(&mut literal)));
+                }
+                tokens.push(Token::Placeholder(Self::parse_placeholder(&mut chars)?));
+            }
+        }
+        if !literal.is_empty() {
+            tokens.push(Token::Literal(literal));
+        }
+
+        Ok(StatusFormat { tokens })
+    }
+
+    fn parse_placeholder(chars: &mut Peekable<Chars>) -> Result<Placeholder, TrackieError> {
+        let mut p = Placeholder::default();
+        while let Some(flag) = chars.next_if(|c| *c == '-' || *c == '0') {
+            match flag {
+                '-' => p.left_align = true,
+                _ => p.zero_pad = true,
+            }
+        }
+        while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+            p.width = p
+                .width
+                .checked_mul(10)
+                .and_then(|w| w.checked_add(digit.to_digit(10).unwrap() as usize))
+                .filter(|w| *w <= MAX_WIDTH)
+                .ok_or_else(|| {
+                    TrackieError::new(
+                        format!("Placeholder widths cannot exceed {}.", MAX_WIDTH).as_str(),
+                    )
+                })?;
+        }
+
+        p.name = match chars.next() {
+            Some(c) if PLACEHOLDERS.contains(c) => c,
+            Some(c) => {
+                return Err(TrackieError::new(
+                    format!("Unknown placeholder '%{}' in format string.", c).as_str(),
+                ))
+            }
+            None => {
+                return Err(TrackieError::new(
+                    "Format string ends with an incomplete '%'.",
+                ))
+            }
+        };
+
+        if chars.next_if_eq(&'{').is_some() {
+            let mut argument = String::new();
+            loop {
+                match chars.next() {
+                    Some('}') => break,
+                    Some(c) => argument.push(c),
+                    None => return Err(TrackieError::new("Missing '}' in format string.")),
+                }
+            }
+            p.argument = Some(argument);
+        }
+        p.validate()?;
+        Ok(p)
+    }
+
+    pub fn render(&self, ctx: &StatusContext) -> String {
+        self.tokens
+            .iter()
+            .map(|t| match t {
+                Token::Literal(l) => l.clone(),
+                Token::Placeholder(p) => p.pad(p.render(ctx)),
+            })
+            .collect()
+    }
+}
+
+impl Placeholder {
+    fn validate(&self) -> Result<(), TrackieError> {
+        match (self.name, &self.argument) {
+            ('d' | 't', Some(a)) if StrftimeItems::new(a).any(|i| i == Item::Error) => Err(
+                TrackieError::new(format!("Invalid time format '{}'.", a).as_str()),
+            ),
+            ('D' | 'T' | 'W' | 'R' | 'B', Some(_)) => {
+                self.duration_format(DurationFormat::Hm).map(|_| ())
+            }
+            ('d' | 't' | 'D' | 'T' | 'W' | 'R' | 'B', _) | (_, None) => Ok(()),
+            (_, Some(_)) => Err(TrackieError::new(
+                format!("Placeholder '%{}' does not take an argument.", self.name).as_str(),
+            )),
+        }
+    }
+
+    fn duration_format(&self, default: DurationFormat) -> Result<DurationFormat, TrackieError> {
+        match &self.argument {
+            None => Ok(default),
+            Some(a) => DurationFormat::from_str(a, true).map_err(|_| {
+                TrackieError::new(format!("Unknown duration format '{}'.", a).as_str())
+            }),
+        }
+    }
+
+    fn render(&self, ctx: &StatusContext) -> String {
+        let duration = |d: Duration| {
+            self.duration_format(ctx.duration_format)
+                .unwrap_or_default()
+                .format(&d)
+        };
+        let time = |default: &str| {
+            let pattern = self.argument.as_deref().unwrap_or(default);
+            ctx.pending.start.format(pattern).to_string()
+        };
+
+        match self.name {
+            'p' => ctx.pending.project_name.clone(),
+            'd' => time("%F"),
+            't' => time("%R"),
+            'D' => duration(ctx.duration),
+            's' => ctx.duration.num_seconds().to_string(),
+            'T' => duration(ctx.today),
+            'W' => duration(ctx.week),
+            'n' => ctx.pending.note.clone().unwrap_or_default(),
+            'g' => ctx.pending.tags.join(","),
+            'R' => ctx
+                .daily_target
+                .map(|t| duration(t - ctx.today))
+                .unwrap_or_default(),
+            'B' => ctx.budget_remaining.map(duration).unwrap_or_default(),
+            _ => unreachable!("Placeholders are validated while parsing"),
+        }
+    }
+
+    fn pad(&self, value: String) -> String {
+        let len = value.chars().count();
+        if len >= self.width {
+            return value;
+        }
+        let fill = self.width - len;
+        match (self.left_align, self.zero_pad) {
+            (true, _) => format!("{}{}", value, " ".repeat(fill)),
+            // The zeros go after the sign of negative durations.
+            (false, true) => match value.strip_prefix('-') {
+                Some(digits) => format!("-{}{}", "0".repeat(fill), digits),
+                None => format!("{}{}", "0".repeat(fill), value),
+            },
+            (false, false) => format!("{}{}", " ".repeat(fill), value),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Local, TimeZone};
+
+    fn pending() -> PendingLog {
+        PendingLog {
+            project_name: "100%p".to_string(),
+            start: Local.with_ymd_and_hms(2000, 1, 1, 9, 5, 0).unwrap(),
+            note: Some("Note".to_string()),
+            tags: vec!["a".to_string(), "b".to_string()],
+        }
+    }
+
+    fn context(pending: &PendingLog) -> StatusContext {
+        StatusContext {
+            pending,
+            duration: Duration::minutes(105),
+            today: Duration::hours(6),
+            week: Duration::hours(30),
+            daily_target: Some(Duration::hours(8)),
+            budget_remaining: Some(Duration::hours(12)),
+            duration_format: DurationFormat::Hm,
+        }
+    }
+
+    fn render(format: &str) -> String {
+        let pending = pending();
+        StatusFormat::parse(format)
+            .unwrap()
+            .render(&context(&pending))
+    }
+
+    #[test]
+    fn json_idle() {
+        let json = serde_json::to_string(&StatusJson::idle()).unwrap();
+        assert_eq!(json, r#"{"tracking":false}"#);
+    }
+
+    #[test]
+    fn json_tracking() {
+        let pending = pending();
+        let json = serde_json::to_value(StatusJson::from(&context(&pending))).unwrap();
+
+        assert_eq!(json["tracking"], true);
+        assert_eq!(json["project"], "100%p");
+        assert_eq!(json["elapsed_seconds"], 6300);
+        assert_eq!(json["note"], "Note");
+        assert_eq!(json["tags"], serde_json::json!(["a", "b"]));
+        assert_eq!(json["today_seconds"], 21600);
+    }
+
+    #[test]
+    fn parse_literal_only() {
+        let f = StatusFormat::parse("abc").unwrap();
+        assert_eq!(f.tokens, vec![Token::Literal("abc".to_string())]);
+        assert!(StatusFormat::parse("").unwrap().tokens.is_empty());
+    }
+
+    #[test]
+    fn parse_placeholder_modifiers() {
+        let f = StatusFormat::parse("%-012D{clock}").unwrap();
+        assert_eq!(
+            f.tokens,
+            vec![Token::Placeholder(Placeholder {
+                name: 'D',
+                argument: Some("clock".to_string()),
+                width: 12,
+                left_align: true,
+                zero_pad: true,
+            })]
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(StatusFormat::parse("%").is_err());
+        assert!(StatusFormat::parse("%x").is_err());
+        assert!(StatusFormat::parse("%10").is_err());
+        assert!(StatusFormat::parse("%D{fortnights}").is_err());
+        assert!(StatusFormat::parse("%p{foo}").is_err());
+        assert!(StatusFormat::parse("%D{clock").is_err());
+        assert!(StatusFormat::parse("%1001p").is_err());
+        assert!(StatusFormat::parse("%99999999999999999999p").is_err());
+        assert!(StatusFormat::parse("%1000p").is_ok());
+    }
+
+    #[test]
+    fn render_placeholders() {
+        assert_eq!(
+            render("%p since %d (%t) [%D]"),
+            "100%p since 2000-01-01 (09:05) [01h 45m]"
+        );
+        assert_eq!(render("%s|%n|%g"), "6300|Note|a,b");
+        assert_eq!(render("%T %W %R"), "06h 00m 30h 00m 02h 00m");
+        assert_eq!(render("%B{decimal}"), "12.00");
+    }
+
+    #[test]
+    fn render_does_not_reinterpret_values() {
+        assert_eq!(render("%p"), "100%p");
+    }
+
+    #[test]
+    fn render_escaped_percent() {
+        assert_eq!(render("100%% %%p"), "100% %p");
+    }
+
+    #[test]
+    fn render_arguments() {
+        assert_eq!(render("%D{decimal}"), "1.75");
+        assert_eq!(render("%t{%H:%M:%S}"), "09:05:00");
+        assert_eq!(render("%d{%a}"), "Sat");
+    }
+
+    #[test]
+    fn render_width_and_padding() {
+        assert_eq!(render("[%6p]"), "[ 100%p]");
+        assert_eq!(render("[%-6p]"), "[100%p ]");
+        assert_eq!(render("[%06s]"), "[006300]");
+        assert_eq!(render("[%2p]"), "[100%p]");
+
+        let zero_padded = Placeholder {
+            width: 6,
+            zero_pad: true,
+            ..Placeholder::default()
+        };
+        assert_eq!(zero_padded.pad("-1:00".to_string()), "-01:00");
+    }
+}
@@ -0,0 +1,120 @@
+use crate::pretty_string::DurationFormat;
+use crate::report_creator::DateRangeReport;
+use crate::TrackieError;
+use chrono::format::{Item, StrftimeItems};
+use chrono::{Duration, NaiveDate};
+use clap::ValueEnum;
+use minijinja::{Environment, ErrorKind, UndefinedBehavior};
+use std::fmt::Write;
+
+/// Renders a user-defined [MiniJinja](https://docs.rs/minijinja) template with the report.
+///
+/// The report is available with the same fields as in the JSON output, e.g. `days`, `total` or
+/// `days[0].projects[0].notes`. Durations are given in minutes and can be formatted with the
+/// `duration` filter, dates with the `date` filter. Using an undefined variable is an error,
+/// optional fields like `rounded` can be checked with `is defined`.
+pub fn render_template(report: &DateRangeReport, template: &str) -> Result<String, TrackieError> {
+    let mut env = Environment::new();
+    env.set_undefined_behavior(UndefinedBehavior::Strict);
+    env.set_keep_trailing_newline(true);
+
+    let default_format = report.duration_format;
+    env.add_filter(
+        "duration",
+        move |minutes: i64, format: Option<String>| -> Result<String, minijinja::Error> {
+            let format = match format {
+                Some(f) => DurationFormat::from_str(&f, true).map_err(|_| {
+                    minijinja::Error::new(
+                        ErrorKind::InvalidOperation,
+                        format!("unknown duration format '{}'", f),
+                    )
+                })?,
+                None => default_format,
+            };
+            Ok(format.format(&Duration::minutes(minutes)))
+        },
+    );
+    env.add_filter(
+        "date",
+        |date: String, format: String| -> Result<String, minijinja::Error> {
+            let date = NaiveDate::parse_from_str(&date, "%F").map_err(|_| {
+                minijinja::Error::new(
+                    ErrorKind::InvalidOperation,
+                    format!("'{}' is not a date", date),
+                )
+            })?;
+            let invalid = || {
+                minijinja::Error::new(
+                    ErrorKind::InvalidOperation,
+                    format!("invalid date format '{}'", format),
+                )
+            };
+            if StrftimeItems::new(&format).any(|i| i == Item::Error) {
+                return Err(invalid());
+            }
+            // Time fields like `%H` are valid, but cannot be formatted for a date.
+            let mut out = String::new();
+            write!(out, "{}", date.format(&format)).map_err(|_| invalid())?;
+            Ok(out)
+        },
+    );
+
+    env.add_template("report", template)
+        .and_then(|_| env.get_template("report")?.render(report))
+        .map_err(|e| TrackieError::new(format!("Invalid template: {:#}", e).as_str()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report_creator::ReportCreator;
+    use crate::time_log::{LogEntry, TimeLog};
+    use chrono::{Local, TimeZone};
+    use std::collections::BTreeMap;
+
+    fn report() -> DateRangeReport {
+        let monday = NaiveDate::from_ymd_opt(2026, 9, 7).unwrap();
+        let start = Local.with_ymd_and_hms(2026, 9, 7, 9, 0, 0).unwrap();
+        let entry = |minutes: i64, note: &str, tags: &[&str]| LogEntry {
+            project_name: "Foo".to_string(),
+            start,
+            end: start + Duration::minutes(minutes),
+            note: Some(note.to_string()),
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+        };
+        let log = TimeLog::new_testing_only(BTreeMap::from([(
+            monday,
+            vec![
+                entry(60, "Review", &["b", "a"]),
+                entry(45, "Fixes", &["a"]),
+                entry(15, "Review", &[]),
+            ],
+        )]));
+        #[allow(deprecated)]
+        let date = Local.ymd(2026, 9, 7);
+        ReportCreator::new(&log).report_days(date, 1, false)
+    }
+
+    #[test]
+    fn render_report_model() {
+        let out = render_template(
+            &report(),
+            "{% for day in days %}{{ day.date | date('%d.%m.') }}\n\
+             {% for p in day.projects %}{{ p.project }};{{ p.duration | duration }};\
+             {{ p.notes | join(', ') }};{{ p.tags | join(',') }}\n{% endfor %}{% endfor %}\
+             Total: {{ total | duration('decimal') }}\n",
+        )
+        .unwrap();
+
+        assert_eq!(out, "07.09.\nFoo;02h 00m;Review, Fixes;a,b\nTotal: 2.00\n");
+    }
+
+    #[test]
+    fn template_errors() {
+        assert!(render_template(&report(), "{% for %}").is_err());
+        assert!(render_template(&report(), "{{ unknown }}").is_err());
+        assert!(render_template(&report(), "{{ total | duration('weeks') }}").is_err());
+        assert!(render_template(&report(), "{{ '2024-01-01' | date('%Q') }}").is_err());
+        assert!(render_template(&report(), "{{ '2024-01-01' | date('%H:%M') }}").is_err());
+    }
+}
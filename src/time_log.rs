@@ -2,9 +2,13 @@ use std::error::Error;
 
 use chrono::{Date, DateTime, Duration, Local, NaiveDate};
 use serde::{Deserialize, Serialize};
+use std::ops::{Add, RangeInclusive};
 
+use crate::calendar::Calendar;
+use crate::project::{is_within, rebase, BudgetStatus, Projects};
 use crate::TrackieError;
-use std::collections::BTreeMap;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 
 type OptError = Result<Option<String>, Box<dyn Error>>;
 
@@ -12,6 +16,10 @@ type OptError = Result<Option<String>, Box<dyn Error>>;
 pub struct PendingLog {
     pub project_name: String,
     pub start: DateTime<Local>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
 }
 
 impl PendingLog {
@@ -21,31 +29,48 @@ impl PendingLog {
     }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct LogEntry {
     pub project_name: String,
     pub start: DateTime<Local>,
     pub end: DateTime<Local>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
 }
 
 impl LogEntry {
-    fn from_time_log(log: &PendingLog, end: DateTime<Local>) -> LogEntry {
+    pub fn from_time_log(log: &PendingLog, end: DateTime<Local>) -> LogEntry {
         LogEntry {
             project_name: (&log.project_name).to_string(),
             start: log.start,
             end,
+            note: log.note.clone(),
+            tags: log.tags.clone(),
         }
     }
 
     pub fn to_duration(&self) -> Duration {
         self.end.signed_duration_since(self.start)
     }
+
+    /// Whether both entries describe the same tracked time, ignoring notes and tags.
+    pub fn same_time_as(&self, other: &LogEntry) -> bool {
+        self.project_name == other.project_name
+            && self.start.timestamp() == other.start.timestamp()
+            && self.end.timestamp() == other.end.timestamp()
+    }
 }
 
 #[derive(Serialize, Deserialize)]
 pub struct TimeLog {
     pub pending: Option<PendingLog>,
     entries: BTreeMap<NaiveDate, Vec<LogEntry>>,
+    #[serde(default, skip_serializing_if = "Calendar::is_empty")]
+    pub calendar: Calendar,
+    #[serde(default, skip_serializing_if = "Projects::is_empty")]
+    pub projects: Projects,
 }
 
 impl Default for TimeLog {
@@ -59,6 +84,8 @@ impl TimeLog {
         TimeLog {
             pending: None,
             entries: BTreeMap::new(),
+            calendar: Calendar::default(),
+            projects: Projects::default(),
         }
     }
 
@@ -71,10 +98,22 @@ impl TimeLog {
         Self {
             pending: None,
             entries,
+            calendar: Calendar::default(),
+            projects: Projects::default(),
         }
     }
 
     pub fn start_log(&mut self, project_name: &str) -> OptError {
+        self.start_annotated_log(project_name, None, Vec::new())
+    }
+
+    /// Like [TimeLog::start_log], but attaches a note and tags to the new log.
+    pub fn start_annotated_log(
+        &mut self,
+        project_name: &str,
+        note: Option<String>,
+        tags: Vec<String>,
+    ) -> OptError {
         let mut warn: Option<String> = None;
         if let Some(p) = &self.pending {
             warn = Some(format!("Stopping time-tracking for {}", p.project_name));
@@ -83,6 +122,8 @@ impl TimeLog {
         let lg = PendingLog {
             project_name: project_name.to_string(),
             start: Local::now(),
+            note,
+            tags,
         };
         self.pending = Some(lg);
         Ok(warn)
@@ -104,12 +145,269 @@ impl TimeLog {
         }
     }
 
+    /// Ends the tracked work at `end`, which lies between its start and now. With `resume`, the
+    /// work is tracked again from now on, leaving out the time in between.
+    pub fn trim_pending(
+        &mut self,
+        end: DateTime<Local>,
+        resume: bool,
+    ) -> Result<LogEntry, TrackieError> {
+        let now = Local::now();
+        let pending = match self.pending.take() {
+            Some(p) if p.start < end && end <= now => p,
+            Some(p) => {
+                self.pending = Some(p);
+                return Err(TrackieError::new(
+                    "The work must end between the start of the tracking and now.",
+                ));
+            }
+            None => return Err(TrackieError::new("No time is currently tracked.")),
+        };
+        let entry = LogEntry::from_time_log(&pending, end);
+        self.add_entry(entry.clone());
+        if resume {
+            self.pending = Some(PendingLog {
+                start: now,
+                ..pending
+            });
+        }
+        Ok(entry)
+    }
+
+    /// Adds a finished entry to the log. Like entries created via [TimeLog::stop_pending], it is
+    /// filed under the day on which it ended.
+    pub fn add_entry(&mut self, entry: LogEntry) {
+        let day = self.entries.entry(entry.end.date_naive()).or_default();
+        let idx = day.partition_point(|e| e.start <= entry.start);
+        day.insert(idx, entry);
+    }
+
+    /// Removes the entry that describes the same time as `entry`. Returns whether one was found.
+    pub fn remove_entry(&mut self, entry: &LogEntry) -> bool {
+        let day = entry.end.date_naive();
+        let Some(entries) = self.entries.get_mut(&day) else {
+            return false;
+        };
+        let len = entries.len();
+        entries.retain(|e| !e.same_time_as(entry));
+        let removed = entries.len() < len;
+        if entries.is_empty() {
+            self.entries.remove(&day);
+        }
+        removed
+    }
+
+    /// Whether the log already contains an entry for the same project and time.
+    pub fn contains(&self, entry: &LogEntry) -> bool {
+        self.entries
+            .get(&entry.end.date_naive())
+            .is_some_and(|v| v.iter().any(|e| e.same_time_as(entry)))
+    }
+
     pub fn for_day(&self, date: Date<Local>) -> &[LogEntry] {
         self.entries
             .get(&date.naive_local())
             .map_or(&[], Vec::as_slice)
     }
 
+    /// Sums up the time tracked on the given days, including the pending log if it is still
+    /// running today.
+    pub fn tracked_time(&self, days: RangeInclusive<NaiveDate>) -> Duration {
+        let logged = self
+            .entries(days.clone())
+            .fold(Duration::zero(), |d, (_, e)| d.add(e.to_duration()));
+        match &self.pending {
+            Some(p) if days.contains(&Local::now().date_naive()) => {
+                logged.add(p.get_pending_duration())
+            }
+            _ => logged,
+        }
+    }
+
+    /// Like [TimeLog::tracked_time], but only sums up the time tracked on the given project and
+    /// its subprojects.
+    pub fn tracked_time_on(&self, project: &str, days: RangeInclusive<NaiveDate>) -> Duration {
+        let logged = self
+            .entries(days.clone())
+            .filter(|(_, e)| is_within(&e.project_name, project))
+            .fold(Duration::zero(), |d, (_, e)| d.add(e.to_duration()));
+        match &self.pending {
+            Some(p)
+                if is_within(&p.project_name, project)
+                    && days.contains(&Local::now().date_naive()) =>
+            {
+                logged.add(p.get_pending_duration())
+            }
+            _ => logged,
+        }
+    }
+
+    /// How much of the budget of the project has been consumed in the period containing `date`.
+    pub fn budget_status(&self, project: &str, date: NaiveDate) -> Option<BudgetStatus> {
+        let budget = self.projects.get(project)?.budget?;
+        let consumed = self.tracked_time_on(project, budget.per.range(date));
+        Some(BudgetStatus::new(budget, consumed))
+    }
+
+    /// The names of all projects that have been tracked or registered.
+    pub fn project_names(&self) -> BTreeSet<&str> {
+        self.entries
+            .values()
+            .flatten()
+            .map(|e| e.project_name.as_str())
+            .chain(self.pending.iter().map(|p| p.project_name.as_str()))
+            .chain(self.projects.iter().map(|(name, _)| name.as_str()))
+            .collect()
+    }
+
+    /// The names of all projects, the most recently tracked first and registered projects that
+    /// were never tracked last.
+    pub fn projects_by_recency(&self) -> Vec<&str> {
+        let mut last_tracked: HashMap<&str, DateTime<Local>> = HashMap::new();
+        for e in self.entries.values().flatten() {
+            let last = last_tracked.entry(&e.project_name).or_insert(e.end);
+            *last = e.end.max(*last);
+        }
+        if let Some(p) = &self.pending {
+            last_tracked.insert(&p.project_name, Local::now());
+        }
+        let mut names: Vec<&str> = self.project_names().into_iter().collect();
+        names.sort_by_key(|name| Reverse(last_tracked.get(name).copied()));
+        names
+    }
+
+    /// The names of all projects that are not archived, ranked by how often and how recently
+    /// they were tracked. The currently tracked project comes first.
+    pub fn projects_by_frecency(&self, now: DateTime<Local>) -> Vec<&str> {
+        let mut scores: HashMap<&str, u32> = HashMap::new();
+        for e in self.entries.values().flatten() {
+            *scores.entry(&e.project_name).or_default() += frecency_weight(now - e.end);
+        }
+        if let Some(p) = &self.pending {
+            scores.insert(&p.project_name, u32::MAX);
+        }
+        let mut names: Vec<&str> = self
+            .project_names()
+            .into_iter()
+            .filter(|name| !self.projects.get(name).is_some_and(|p| p.archived))
+            .collect();
+        names.sort_by_key(|name| Reverse(scores.get(name).copied().unwrap_or_default()));
+        names
+    }
+
+    /// Whether `name` is a tracked or registered project, or an alias of one.
+    pub fn is_known_project(&self, name: &str) -> bool {
+        self.project_names().contains(name) || self.projects.alias_of(name).is_some()
+    }
+
+    /// The number of entries tracked on a project and its subprojects.
+    pub fn count_entries_within(&self, project: &str) -> usize {
+        self.entries
+            .values()
+            .flatten()
+            .filter(|e| is_within(&e.project_name, project))
+            .count()
+    }
+
+    /// Renames a project and its subprojects in all entries, the pending log and the project
+    /// metadata.
+    ///
+    /// Returns the number of renamed entries.
+    pub fn rename_project(&mut self, from: &str, to: &str) -> Result<usize, TrackieError> {
+        self.check_rename(from, to)?;
+        Ok(self.move_project(from, to))
+    }
+
+    /// Fails if [TimeLog::rename_project] would fail.
+    pub fn check_rename(&self, from: &str, to: &str) -> Result<(), TrackieError> {
+        self.check_projects_exist(&[from])?;
+        if self.project_names().iter().any(|n| is_within(n, to))
+            || self.projects.alias_of(to).is_some()
+        {
+            return Err(TrackieError::new(
+                format!(
+                    "Project {} already exists. Use `project merge` to combine projects.",
+                    to
+                )
+                .as_str(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Moves the entries of several projects and their subprojects to `target`, which may
+    /// already exist. Nothing is changed if one of the projects does not exist.
+    ///
+    /// Returns the number of moved entries.
+    pub fn merge_projects(
+        &mut self,
+        sources: &[&str],
+        target: &str,
+    ) -> Result<usize, TrackieError> {
+        self.check_merge(sources, target)?;
+        Ok(sources.iter().map(|s| self.move_project(s, target)).sum())
+    }
+
+    /// Fails if [TimeLog::merge_projects] would fail.
+    pub fn check_merge(&self, sources: &[&str], target: &str) -> Result<(), TrackieError> {
+        self.check_projects_exist(sources)?;
+        if let Some(source) = sources.iter().find(|s| is_within(target, s)) {
+            return Err(TrackieError::new(
+                format!("Cannot merge project {} into {}.", source, target).as_str(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn check_projects_exist(&self, projects: &[&str]) -> Result<(), TrackieError> {
+        let names = self.project_names();
+        match projects
+            .iter()
+            .find(|p| !names.iter().any(|n| is_within(n, p)))
+        {
+            Some(unknown) => Err(TrackieError::new(
+                format!("Unknown project {}.", unknown).as_str(),
+            )),
+            None => Ok(()),
+        }
+    }
+
+    fn move_project(&mut self, from: &str, to: &str) -> usize {
+        let mut moved = 0;
+        for entry in self.entries.values_mut().flatten() {
+            if let Some(name) = rebase(&entry.project_name, from, to) {
+                entry.project_name = name;
+                moved += 1;
+            }
+        }
+        if let Some(p) = self.pending.as_mut() {
+            if let Some(name) = rebase(&p.project_name, from, to) {
+                p.project_name = name;
+            }
+        }
+        self.projects.rename(from, to);
+        moved
+    }
+
+    /// Returns all entries logged on the given days, together with the day they were logged on.
+    /// An inverted range contains no days.
+    pub fn entries(
+        &self,
+        days: RangeInclusive<NaiveDate>,
+    ) -> impl Iterator<Item = (&NaiveDate, &LogEntry)> {
+        // `BTreeMap::range` panics if the start lies after the end.
+        (days.start() <= days.end())
+            .then_some(days)
+            .into_iter()
+            .flat_map(|days| self.entries.range(days))
+            .flat_map(|(d, v)| v.iter().map(move |e| (d, e)))
+    }
+
+    /// Returns the date of the earliest [LogEntry] in the log.
+    pub fn first_date(&self) -> Option<NaiveDate> {
+        self.entries.keys().next().copied()
+    }
+
     /// Returns the [LogEntry] that was added last to the log.
     pub fn get_latest_entry(&self) -> Option<&LogEntry> {
         let max_date = self.entries.iter().max_by_key(|(k, _)| *k).map(|(_, v)| v);
@@ -120,11 +418,23 @@ impl TimeLog {
     }
 }
 
+/// The weight of a single entry that ended `age` ago, for ranking projects by frecency.
+fn frecency_weight(age: Duration) -> u32 {
+    match age.num_days() {
+        ..=3 => 100,
+        4..=14 => 70,
+        15..=31 => 50,
+        32..=90 => 30,
+        _ => 10,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use chrono::{Datelike, TimeZone};
 
     use super::*;
+    use crate::project::{Budget, BudgetPeriod};
     use std::iter::FromIterator;
 
     #[test]
@@ -197,9 +507,177 @@ mod tests {
         assert!(deserialized.entries.is_empty());
     }
 
+    #[test]
+    fn tracked_time_in_range() {
+        let lg = create_tl_with_two_dates();
+        let first = test_date().with_day(1).unwrap().naive_local();
+        let second = test_date().with_day(2).unwrap().naive_local();
+
+        assert_eq!(lg.tracked_time(first..=first), Duration::minutes(30));
+        assert_eq!(lg.tracked_time(first..=second), Duration::minutes(120));
+    }
+
+    #[test]
+    fn entries_in_range() {
+        let lg = create_tl_with_two_dates();
+        let second = test_date().with_day(2).unwrap().naive_local();
+
+        let entries: Vec<_> = lg.entries(second..=second).collect();
+        assert_eq!(entries.len(), 2);
+        assert!(entries.iter().all(|(d, _)| **d == second));
+        assert_eq!(lg.entries(NaiveDate::MIN..=NaiveDate::MAX).count(), 3);
+    }
+
+    #[test]
+    fn add_entry_keeps_order() {
+        let mut lg = create_tl_with_two_dates();
+        let mut entry = create_log(2, 10, "Early");
+        entry.start -= Duration::hours(1);
+        assert!(!lg.contains(&entry));
+
+        lg.add_entry(entry.clone());
+
+        assert!(lg.contains(&entry));
+        let day = lg.for_day(test_date().with_day(2).unwrap());
+        assert_eq!(day.len(), 3);
+        assert_eq!(day[0].project_name, "Early");
+    }
+
+    #[test]
+    fn tracked_time_includes_pending() {
+        let mut lg = TimeLog::new();
+        lg.start_annotated_log("ABC", Some("Note".to_string()), vec!["tag".to_string()])
+            .unwrap();
+        let today = Local::now().date_naive();
+
+        assert!(lg.tracked_time(today..=today) >= Duration::zero());
+        assert_eq!(lg.pending.as_ref().unwrap().tags, vec!["tag"]);
+
+        let stopped = lg.stop_pending().unwrap();
+        assert_eq!(stopped.note.as_deref(), Some("Note"));
+        assert_eq!(lg.get_latest_entry().unwrap().tags, vec!["tag"]);
+    }
+
+    #[test]
+    fn budget_of_project() {
+        let mut lg = create_tl_with_two_dates();
+        let day = test_date().naive_local();
+        assert!(lg.budget_status("First", day).is_none());
+
+        lg.projects.get_or_insert("First").budget = Some(Budget {
+            limit: Duration::hours(1),
+            per: BudgetPeriod::Total,
+        });
+        let status = lg.budget_status("First", day).unwrap();
+        assert_eq!(status.consumed, Duration::minutes(40));
+        assert_eq!(status.remaining, Duration::minutes(20));
+        assert_eq!(lg.tracked_time_on("First", day..=day), Duration::zero());
+    }
+
+    #[test]
+    fn rename_project() {
+        let mut lg = create_tl_with_two_dates();
+        lg.projects.get_or_insert("First").aliases = vec!["1st".to_string()];
+
+        assert!(lg.rename_project("Unknown", "Other").is_err());
+        assert!(lg.rename_project("First", "Second").is_err());
+        assert!(lg.rename_project("First", "1st").is_err());
+
+        assert_eq!(lg.rename_project("First", "Renamed").unwrap(), 1);
+        assert!(lg.project_names().contains("Renamed"));
+        assert!(!lg.project_names().contains("First"));
+        assert_eq!(lg.projects.resolve("1st"), "Renamed");
+    }
+
+    #[test]
+    fn projects_by_recency() {
+        let mut lg = create_tl_with_two_dates();
+        lg.projects.get_or_insert("Registered");
+        assert_eq!(
+            lg.projects_by_recency(),
+            ["Second", "First", "Target", "Registered"]
+        );
+
+        lg.start_log("Target").unwrap();
+        assert_eq!(lg.projects_by_recency()[0], "Target");
+    }
+
+    #[test]
+    fn projects_by_frecency() {
+        let mut lg = create_tl_with_two_dates();
+        lg.add_entry(create_log(1, 20, "Target"));
+        lg.projects.get_or_insert("Archived").archived = true;
+        let now = test_date().with_day(20).unwrap().and_hms(12, 0, 0);
+        assert_eq!(lg.projects_by_frecency(now), ["Target", "First", "Second"]);
+
+        let later = test_date().with_day(31).unwrap().and_hms(12, 0, 0);
+        lg.add_entry(create_log(30, 10, "Second"));
+        assert_eq!(
+            lg.projects_by_frecency(later),
+            ["Second", "Target", "First"]
+        );
+    }
+
+    #[test]
+    fn remove_entry() {
+        let mut lg = create_tl_with_two_dates();
+        assert!(lg.remove_entry(&create_log(1, 30, "Target")));
+        assert!(!lg.remove_entry(&create_log(1, 30, "Target")));
+        assert!(!lg.remove_entry(&create_log(2, 30, "First")));
+        assert_eq!(
+            lg.first_date(),
+            test_date().with_day(2).map(|d| d.naive_local())
+        );
+    }
+
+    #[test]
+    fn trim_pending() {
+        let mut lg = TimeLog::new();
+        let start = Local::now() - Duration::hours(10);
+        lg.pending = Some(PendingLog {
+            project_name: "Foo".to_string(),
+            start,
+            note: Some("Lunch".to_string()),
+            tags: Vec::new(),
+        });
+        let end = start + Duration::hours(3);
+
+        assert!(lg.trim_pending(start, false).is_err());
+        assert!(lg
+            .trim_pending(Local::now() + Duration::hours(1), false)
+            .is_err());
+        let entry = lg.trim_pending(end, true).unwrap();
+        assert_eq!(entry.to_duration(), Duration::hours(3));
+        assert!(lg.contains(&entry));
+        let resumed = lg.pending.clone().unwrap();
+        assert!(resumed.start > end + Duration::hours(6));
+        assert_eq!(resumed.note.as_deref(), Some("Lunch"));
+        assert!(lg.trim_pending(resumed.start, false).is_err());
+    }
+
+    #[test]
+    fn merge_projects() {
+        let mut lg = create_tl_with_two_dates();
+        lg.add_entry(create_log(2, 10, "First/sub"));
+
+        assert!(lg.merge_projects(&["First", "Unknown"], "Target").is_err());
+        assert_eq!(lg.count_entries_within("First"), 2);
+        assert!(lg.merge_projects(&["First"], "First/new").is_err());
+
+        assert_eq!(
+            lg.merge_projects(&["First", "Second"], "Target").unwrap(),
+            3
+        );
+        let names: Vec<&str> = lg.project_names().into_iter().collect();
+        assert_eq!(names, ["Target", "Target/sub"]);
+        assert_eq!(lg.count_entries_within("Target"), 4);
+    }
+
     fn create_tl_with_two_dates() -> TimeLog {
         TimeLog {
             pending: None,
+            calendar: Calendar::default(),
+            projects: Projects::default(),
             entries: BTreeMap::from_iter(vec![
                 (
                     test_date().with_day(1).unwrap().naive_local(),
@@ -218,6 +696,8 @@ mod tests {
             start: test_date().with_day(day).unwrap().and_hms(4, 0, 20),
             end: test_date().with_day(day).unwrap().and_hms(4, dur, 20),
             project_name: name.to_string(),
+            note: None,
+            tags: Vec::new(),
         }
     }
 
@@ -0,0 +1,605 @@
+use crate::config::Config;
+use crate::events::{run_hooks, TrackingEvent};
+use crate::persistence::{save_log, FileHandler};
+use crate::picker::filter;
+use crate::pretty_string::DurationFormat;
+use crate::time_log::{LogEntry, TimeLog};
+use crate::{start_project, TrackieError};
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, NaiveTime, TimeZone};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Bar, BarChart, BarGroup, Block, List, ListItem, ListState, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::io::{self, IsTerminal};
+use std::time::SystemTime;
+
+/// How often the running timer is redrawn and the data file is checked for changes.
+const TICK: std::time::Duration = std::time::Duration::from_secs(1);
+const HELP: &str = "s start  w switch  x stop  p pause/resume  a add  e edit  ↑↓ select  q quit";
+
+/// Runs the full-screen terminal UI until the user quits. Every change is saved right away.
+pub fn run_tui(fh: &mut dyn FileHandler, config: &Config) -> Result<(), TrackieError> {
+    if !io::stdin().is_terminal() || !io::stdout().is_terminal() {
+        return Err(TrackieError::new(
+            "The terminal UI needs an interactive terminal.",
+        ));
+    }
+    let mut app = App::new(config);
+    app.reload(fh, true)?;
+    let mut terminal = ratatui::try_init()?;
+    let result = app.run(&mut terminal, fh);
+    ratatui::restore();
+    result
+}
+
+struct App<'a> {
+    log: TimeLog,
+    /// The content of the data file when it was last read or written.
+    content: Option<String>,
+    /// The modification time of the data file when it was last read or written.
+    modified: Option<SystemTime>,
+    config: &'a Config,
+    format: DurationFormat,
+    selected: usize,
+    input: Option<Input>,
+    message: Option<Result<String, String>>,
+}
+
+struct Input {
+    kind: InputKind,
+    text: String,
+}
+
+enum InputKind {
+    Start,
+    Add,
+    Edit(LogEntry),
+}
+
+impl<'a> App<'a> {
+    fn new(config: &'a Config) -> Self {
+        Self {
+            log: TimeLog::default(),
+            content: None,
+            modified: None,
+            config,
+            format: config.duration_format.unwrap_or_default(),
+            selected: 0,
+            input: None,
+            message: None,
+        }
+    }
+
+    fn run(
+        &mut self,
+        terminal: &mut DefaultTerminal,
+        fh: &mut dyn FileHandler,
+    ) -> Result<(), TrackieError> {
+        loop {
+            if let Err(e) = self.reload(fh, false) {
+                self.message = Some(Err(e.to_string()));
+            }
+            terminal.draw(|frame| self.draw(frame))?;
+            if !event::poll(TICK)? {
+                continue;
+            }
+            // Resizing needs no handling, as the next draw adapts to the new size.
+            if let Event::Key(key) = event::read()? {
+                if key.kind != KeyEventKind::Release && !self.handle_key(key, fh) {
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    /// Reads the data file again if it was changed by someone else, e.g. another trackie call.
+    /// The file is only read if its modification time changed, or if that time is unknown.
+    fn reload(&mut self, fh: &dyn FileHandler, force: bool) -> Result<(), TrackieError> {
+        let modified = fh.modified();
+        if !force && modified.is_some() && modified == self.modified {
+            return Ok(());
+        }
+        self.modified = modified;
+        let content = fh.read_file()?;
+        if !force && content == self.content {
+            return Ok(());
+        }
+        self.log = match &content {
+            Some(c) => TimeLog::from_json(c)?,
+            None => TimeLog::default(),
+        };
+        if !force {
+            self.message = Some(Ok("Reloaded the changed data file.".to_string()));
+        }
+        self.content = content;
+        self.selected = self.selected.min(self.today().len().saturating_sub(1));
+        Ok(())
+    }
+
+    /// Applies a change to the log and saves it. If that fails, the log is read again from disk.
+    fn modify(
+        &mut self,
+        fh: &mut dyn FileHandler,
+        change: impl FnOnce(&mut TimeLog) -> Result<String, Box<dyn Error>>,
+    ) {
+        self.modify_as(fh, false, change)
+    }
+
+    /// Like [App::modify], but reports stopping the tracking to the hooks as a pause if `pause`.
+    fn modify_as(
+        &mut self,
+        fh: &mut dyn FileHandler,
+        pause: bool,
+        change: impl FnOnce(&mut TimeLog) -> Result<String, Box<dyn Error>>,
+    ) {
+        let tracked_before = self.log.pending.clone();
+        let result = change(&mut self.log).and_then(|msg| save_log(fh, &self.log).map(|_| msg));
+        match result {
+            Ok(msg) => {
+                self.content = serde_json::to_string(&self.log).ok();
+                self.modified = fh.modified();
+                self.message = Some(Ok(msg));
+                if let Some(mut event) = TrackingEvent::between(tracked_before.as_ref(), &self.log)
+                {
+                    if pause {
+                        event = event.into_pause();
+                    }
+                    // The message line only fits one of the failed hooks.
+                    if let Some(warning) = run_hooks(&self.config.hooks, &event).into_iter().last()
+                    {
+                        self.message = Some(Err(warning));
+                    }
+                }
+            }
+            Err(e) => {
+                self.message = Some(Err(e.to_string()));
+                if let Err(e) = self.reload(fh, true) {
+                    self.message = Some(Err(e.to_string()));
+                }
+            }
+        }
+        self.selected = self.selected.min(self.today().len().saturating_sub(1));
+    }
+
+    /// Handles a key press. Returns `false` once the user quits.
+    fn handle_key(&mut self, key: KeyEvent, fh: &mut dyn FileHandler) -> bool {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            return false;
+        }
+        if self.input.is_some() {
+            self.handle_input_key(key, fh);
+            return true;
+        }
+        self.message = None;
+        let format = self.format;
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            KeyCode::Char('s') => self.open_input(InputKind::Start, String::new()),
+            KeyCode::Char('w') if self.log.pending.is_none() => {
+                self.message = Some(Err("No time is currently tracked.".to_string()))
+            }
+            KeyCode::Char('w') => self.open_input(InputKind::Start, String::new()),
+            KeyCode::Char('x') => self.modify(fh, |log| {
+                let pending = log.stop_pending()?;
+                Ok(format!(
+                    "Tracked {} on project {}",
+                    format.format(&pending.get_pending_duration()),
+                    pending.project_name
+                ))
+            }),
+            KeyCode::Char('p') => self.modify_as(fh, true, |log| {
+                if log.pending.is_some() {
+                    let pending = log.stop_pending()?;
+                    return Ok(format!("Paused project {}", pending.project_name));
+                }
+                let latest = log
+                    .get_latest_entry()
+                    .ok_or_else(|| TrackieError::new("Nothing to resume."))?;
+                let (project, tags) = (latest.project_name.clone(), latest.tags.clone());
+                log.start_annotated_log(&project, None, tags)?;
+                Ok(format!("Resumed project {}", project))
+            }),
+            KeyCode::Char('a') => self.open_input(InputKind::Add, String::new()),
+            KeyCode::Char('e') => match self.today().get(self.selected).copied().cloned() {
+                Some(entry) => {
+                    let text = format!(
+                        "{} {}-{}",
+                        entry.project_name,
+                        entry.start.format("%H:%M"),
+                        entry.end.format("%H:%M")
+                    );
+                    self.open_input(InputKind::Edit(entry), text)
+                }
+                None => self.message = Some(Err("There is no entry to edit.".to_string())),
+            },
+            KeyCode::Up | KeyCode::Char('k') => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') if self.selected + 1 < self.today().len() => {
+                self.selected += 1
+            }
+            _ => {}
+        }
+        true
+    }
+
+    fn open_input(&mut self, kind: InputKind, text: String) {
+        self.input = Some(Input { kind, text });
+    }
+
+    fn handle_input_key(&mut self, key: KeyEvent, fh: &mut dyn FileHandler) {
+        let Some(input) = &mut self.input else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc => self.input = None,
+            KeyCode::Enter => {
+                let input = self.input.take().unwrap();
+                self.submit(input, fh);
+            }
+            KeyCode::Backspace => {
+                input.text.pop();
+            }
+            KeyCode::Tab if matches!(input.kind, InputKind::Start) => {
+                let projects = self.log.projects_by_frecency(Local::now());
+                if let Some(project) = filter(&input.text, &projects).first() {
+                    input.text = project.to_string();
+                }
+            }
+            KeyCode::Char(c) => input.text.push(c),
+            _ => {}
+        }
+    }
+
+    fn submit(&mut self, input: Input, fh: &mut dyn FileHandler) {
+        let text = input.text.trim();
+        match input.kind {
+            InputKind::Start => {
+                if text.is_empty() {
+                    self.message = Some(Err("No project given.".to_string()));
+                    return;
+                }
+                let config = self.config;
+                self.modify(fh, |log| {
+                    let (project, warnings) = start_project(log, text, None, Vec::new(), config)?;
+                    // The message line shows the warnings in front of the usual message.
+                    Ok(warnings
+                        .iter()
+                        .map(|w| w.trim_end_matches('.').to_string())
+                        .chain([format!("Tracking time for project {}", project)])
+                        .collect::<Vec<_>>()
+                        .join(". "))
+                });
+            }
+            InputKind::Add => self.modify(fh, |log| {
+                let entry = parse_entry(text, Local::now().date_naive())?;
+                let msg = format!("Added entry for project {}", entry.project_name);
+                log.add_entry(entry);
+                Ok(msg)
+            }),
+            InputKind::Edit(old) => self.modify(fh, |log| {
+                let entry = LogEntry {
+                    note: old.note.clone(),
+                    tags: old.tags.clone(),
+                    ..parse_entry(text, old.end.date_naive())?
+                };
+                if !log.remove_entry(&old) {
+                    return Err(TrackieError::new("The entry no longer exists.").into());
+                }
+                let msg = format!("Updated entry for project {}", entry.project_name);
+                log.add_entry(entry);
+                Ok(msg)
+            }),
+        }
+    }
+
+    fn today(&self) -> Vec<&LogEntry> {
+        let today = Local::now().date_naive();
+        self.log.entries(today..=today).map(|(_, e)| e).collect()
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let [timer, main, footer] = Layout::vertical([
+            Constraint::Length(4),
+            Constraint::Min(8),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        let [entries, right] =
+            Layout::horizontal([Constraint::Percentage(55), Constraint::Percentage(45)])
+                .areas(main);
+        let [week, projects] =
+            Layout::vertical([Constraint::Length(10), Constraint::Min(3)]).areas(right);
+
+        self.draw_timer(frame, timer);
+        self.draw_entries(frame, entries);
+        self.draw_week(frame, week);
+        self.draw_projects(frame, projects);
+        self.draw_footer(frame, footer);
+    }
+
+    fn draw_timer(&self, frame: &mut Frame, area: Rect) {
+        let today = Local::now().date_naive();
+        let running = match &self.log.pending {
+            Some(p) => Line::from(vec![
+                Span::from(p.project_name.as_str()).bold().green(),
+                Span::from(format!(" since {} ", p.start.format("%H:%M"))),
+                Span::from(self.format.format(&p.get_pending_duration())).bold(),
+                Span::from(
+                    p.note
+                        .as_deref()
+                        .map(|n| format!("  {}", n))
+                        .unwrap_or_default(),
+                )
+                .italic(),
+            ]),
+            None => Line::from("Not tracking").dim(),
+        };
+        let totals = Line::from(format!(
+            "Today {}  Week {}",
+            self.format.format(&self.log.tracked_time(today..=today)),
+            self.format
+                .format(&self.log.tracked_time(week_start(today)..=today))
+        ));
+        let block = Block::bordered().title(" trackie ");
+        frame.render_widget(Paragraph::new(vec![running, totals]).block(block), area);
+    }
+
+    fn draw_entries(&self, frame: &mut Frame, area: Rect) {
+        let mut items: Vec<ListItem> = self
+            .today()
+            .into_iter()
+            .map(|e| {
+                ListItem::new(format!(
+                    "{}-{}  {:>9}  {}{}",
+                    e.start.format("%H:%M"),
+                    e.end.format("%H:%M"),
+                    self.format.format(&e.to_duration()),
+                    e.project_name,
+                    e.note
+                        .as_deref()
+                        .map(|n| format!("  {}", n))
+                        .unwrap_or_default()
+                ))
+            })
+            .collect();
+        if let Some(p) = &self.log.pending {
+            items.push(
+                ListItem::new(format!(
+                    "{}-       {:>9}  {}",
+                    p.start.format("%H:%M"),
+                    self.format.format(&p.get_pending_duration()),
+                    p.project_name
+                ))
+                .green(),
+            );
+        }
+        let list = List::new(items)
+            .block(Block::bordered().title(" Today "))
+            .highlight_style(Style::new().reversed());
+        let mut state = ListState::default().with_selected(Some(self.selected));
+        frame.render_stateful_widget(list, area, &mut state);
+    }
+
+    fn draw_week(&self, frame: &mut Frame, area: Rect) {
+        let start = week_start(Local::now().date_naive());
+        let bars: Vec<Bar> = (0..7)
+            .map(|offset| {
+                let day = start + Duration::days(offset);
+                let tracked = self.log.tracked_time(day..=day);
+                Bar::default()
+                    .value(tracked.num_minutes().max(0) as u64)
+                    .text_value(short_duration(&tracked))
+                    .label(Line::from(day.format("%a").to_string()))
+            })
+            .collect();
+        let chart = BarChart::default()
+            .block(Block::bordered().title(" This week "))
+            .bar_width(5)
+            .bar_gap(1)
+            .data(BarGroup::default().bars(&bars));
+        frame.render_widget(chart, area);
+    }
+
+    fn draw_projects(&self, frame: &mut Frame, area: Rect) {
+        let today = Local::now().date_naive();
+        let mut totals: BTreeMap<&str, Duration> = BTreeMap::new();
+        for (_, e) in self.log.entries(week_start(today)..=today) {
+            let total = totals.entry(&e.project_name).or_insert_with(Duration::zero);
+            *total = *total + e.to_duration();
+        }
+        if let Some(p) = &self.log.pending {
+            let total = totals.entry(&p.project_name).or_insert_with(Duration::zero);
+            *total = *total + p.get_pending_duration();
+        }
+        let mut totals: Vec<(&str, Duration)> = totals.into_iter().collect();
+        totals.sort_by_key(|(_, d)| Reverse(*d));
+        let items: Vec<ListItem> = totals
+            .iter()
+            .map(|(project, d)| ListItem::new(format!("{:>9}  {}", self.format.format(d), project)))
+            .collect();
+        let list = List::new(items).block(Block::bordered().title(" Projects this week "));
+        frame.render_widget(list, area);
+    }
+
+    fn draw_footer(&self, frame: &mut Frame, area: Rect) {
+        let line = match (&self.input, &self.message) {
+            (Some(input), _) => {
+                let title = match input.kind {
+                    InputKind::Start if self.log.pending.is_some() => "Switch to project",
+                    InputKind::Start => "Start project",
+                    InputKind::Add => "Add entry (project HH:MM-HH:MM)",
+                    InputKind::Edit(_) => "Edit entry (project HH:MM-HH:MM)",
+                };
+                Line::from(format!("{}: {}_", title, input.text))
+            }
+            (None, Some(Ok(msg))) => Line::from(msg.as_str()),
+            (None, Some(Err(msg))) => Line::from(msg.as_str()).red(),
+            (None, None) => Line::from(HELP).dim(),
+        };
+        frame.render_widget(Paragraph::new(line), area);
+    }
+}
+
+fn week_start(date: NaiveDate) -> NaiveDate {
+    date - Duration::days(date.weekday().num_days_from_monday() as i64)
+}
+
+/// Formats a duration short enough to fit into a bar of the week chart.
+fn short_duration(d: &Duration) -> String {
+    format!("{}:{:02}", d.num_hours(), d.num_minutes() % 60)
+}
+
+/// Parses an entry given as `<project> <HH:MM>-<HH:MM>` on the given day.
+fn parse_entry(text: &str, date: NaiveDate) -> Result<LogEntry, TrackieError> {
+    let invalid = || TrackieError::new("Entries are given as `<project> <HH:MM>-<HH:MM>`.");
+    let (project, times) = text.trim().rsplit_once(' ').ok_or_else(invalid)?;
+    let (start, end) = times.split_once('-').ok_or_else(invalid)?;
+    let local_time = |time: &str| -> Result<DateTime<Local>, TrackieError> {
+        let time = NaiveTime::parse_from_str(time, "%H:%M").map_err(|_| invalid())?;
+        Local
+            .from_local_datetime(&date.and_time(time))
+            .earliest()
+            .ok_or_else(|| TrackieError::new("Non-existent local time."))
+    };
+    let (start, end) = (local_time(start)?, local_time(end)?);
+    if end <= start {
+        return Err(TrackieError::new("The entry must end after it starts."));
+    }
+    Ok(LogEntry {
+        project_name: project.trim().to_string(),
+        start,
+        end,
+        note: None,
+        tags: Vec::new(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct MemoryFileHandler {
+        content: Option<String>,
+        modified: Option<SystemTime>,
+    }
+
+    impl FileHandler for MemoryFileHandler {
+        fn read_file(&self) -> Result<Option<String>, Box<dyn Error>> {
+            Ok(self.content.clone())
+        }
+
+        fn write_file(&mut self, content: &str) -> Result<(), Box<dyn Error>> {
+            self.content = Some(content.to_string());
+            Ok(())
+        }
+
+        fn modified(&self) -> Option<SystemTime> {
+            self.modified
+        }
+    }
+
+    fn press(app: &mut App, fh: &mut MemoryFileHandler, keys: &str) {
+        for c in keys.chars() {
+            let code = match c {
+                '\n' => KeyCode::Enter,
+                '\t' => KeyCode::Tab,
+                c => KeyCode::Char(c),
+            };
+            assert!(app.handle_key(KeyEvent::new(code, KeyModifiers::NONE), fh));
+        }
+    }
+
+    #[test]
+    fn parse_entries() {
+        let date = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+        let entry = parse_entry("acme web 09:00-10:30", date).unwrap();
+        assert_eq!(entry.project_name, "acme web");
+        assert_eq!(entry.to_duration(), Duration::minutes(90));
+        assert!(parse_entry("acme 10:30-09:00", date).is_err());
+        assert!(parse_entry("acme 9-10", date).is_err());
+        assert!(parse_entry("09:00-10:00", date).is_err());
+    }
+
+    #[test]
+    fn track_with_keys() {
+        let mut fh = MemoryFileHandler::default();
+        let config = Config::default();
+        let mut app = App::new(&config);
+        app.reload(&fh, true).unwrap();
+
+        press(&mut app, &mut fh, "sweb\n");
+        assert_eq!(app.log.pending.as_ref().unwrap().project_name, "web");
+        press(&mut app, &mut fh, "p");
+        assert!(app.log.pending.is_none());
+        press(&mut app, &mut fh, "p");
+        assert_eq!(app.log.pending.as_ref().unwrap().project_name, "web");
+        press(&mut app, &mut fh, "x");
+        assert!(app.log.pending.is_none());
+        press(&mut app, &mut fh, "sw\t\n");
+        assert_eq!(app.log.pending.as_ref().unwrap().project_name, "web");
+
+        let saved = TimeLog::from_json(fh.content.as_deref().unwrap()).unwrap();
+        assert_eq!(saved.pending.unwrap().project_name, "web");
+        assert_eq!(app.today().len(), 2);
+    }
+
+    #[test]
+    fn start_like_the_cli() {
+        let mut fh = MemoryFileHandler::default();
+        let config = Config::from_toml("unknown_projects = \"refuse\"").unwrap();
+        let mut log = TimeLog::default();
+        let project = log.projects.get_or_insert("client-x");
+        project.aliases.push("cx".to_string());
+        fh.content = Some(serde_json::to_string(&log).unwrap());
+        let mut app = App::new(&config);
+        app.reload(&fh, true).unwrap();
+
+        press(&mut app, &mut fh, "sweb\n");
+        assert!(app.log.pending.is_none());
+        assert!(matches!(&app.message, Some(Err(e)) if e.starts_with("Unknown project web.")));
+        press(&mut app, &mut fh, "scx\n");
+        assert_eq!(app.log.pending.as_ref().unwrap().project_name, "client-x");
+    }
+
+    #[test]
+    fn reload_changes_on_disk() {
+        let mut fh = MemoryFileHandler::default();
+        let config = Config::default();
+        let mut app = App::new(&config);
+        app.reload(&fh, true).unwrap();
+        press(&mut app, &mut fh, "sweb\n");
+
+        let mut other = TimeLog::default();
+        other.start_log("api").unwrap();
+        fh.content = Some(serde_json::to_string(&other).unwrap());
+        app.reload(&fh, false).unwrap();
+        assert_eq!(app.log.pending.as_ref().unwrap().project_name, "api");
+        assert!(matches!(app.message, Some(Ok(_))));
+    }
+
+    #[test]
+    fn reload_only_modified_file() {
+        let mut fh = MemoryFileHandler {
+            modified: Some(SystemTime::UNIX_EPOCH),
+            ..MemoryFileHandler::default()
+        };
+        let config = Config::default();
+        let mut app = App::new(&config);
+        app.reload(&fh, true).unwrap();
+
+        let mut other = TimeLog::default();
+        other.start_log("api").unwrap();
+        fh.content = Some(serde_json::to_string(&other).unwrap());
+        app.reload(&fh, false).unwrap();
+        assert!(app.log.pending.is_none());
+
+        fh.modified = Some(SystemTime::now());
+        app.reload(&fh, false).unwrap();
+        assert_eq!(app.log.pending.as_ref().unwrap().project_name, "api");
+    }
+}
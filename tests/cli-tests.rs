@@ -1,4 +1,5 @@
 use assert_cmd::Command;
+use predicates::prelude::*;
 use rand::Rng;
 use std::error::Error;
 use std::path::PathBuf;
@@ -49,6 +50,83 @@ fn test_status_format() {
         .stdout("foo\n");
 }
 
+#[test]
+fn test_status_duration_format() {
+    let t = TestDirectory::create();
+
+    cmd(&t).arg("start").arg("foo").assert().success();
+
+    cmd(&t)
+        .arg("status")
+        .arg("-f")
+        .arg("%D{clock}|%D{iso8601}")
+        .assert()
+        .stdout("0:00|PT0S\n");
+
+    cmd(&t)
+        .arg("status")
+        .arg("-f")
+        .arg("%D")
+        .arg("--duration-format")
+        .arg("decimal")
+        .assert()
+        .stdout("0.00\n");
+}
+
+#[test]
+fn test_status_note_and_tags() {
+    let t = TestDirectory::create();
+
+    cmd(&t)
+        .args([
+            "start",
+            "100%p",
+            "--note",
+            "Fixing bugs",
+            "-t",
+            "dev",
+            "-t",
+            "ops",
+        ])
+        .assert()
+        .success();
+
+    cmd(&t)
+        .args(["status", "-f", "%p|%n|%g|%%|%-4s|"])
+        .assert()
+        .stdout("100%p|Fixing bugs|dev,ops|%|0   |\n");
+
+    cmd(&t).args(["status", "-f", "%x"]).assert().failure();
+}
+
+#[test]
+fn test_status_json_output() -> Result<(), Box<dyn Error>> {
+    let t = TestDirectory::create();
+
+    cmd(&t)
+        .args(["status", "--json"])
+        .assert()
+        .stdout(predicates::str::contains(r#""tracking": false"#))
+        .code(3);
+
+    cmd(&t)
+        .args(["start", "foo", "-n", "Note", "-t", "dev"])
+        .assert()
+        .success();
+
+    let out = cmd(&t).args(["status", "--json"]).output()?;
+    assert!(out.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&out.stdout)?;
+    assert_eq!(json["tracking"], true);
+    assert_eq!(json["project"], "foo");
+    assert_eq!(json["note"], "Note");
+    assert_eq!(json["tags"], serde_json::json!(["dev"]));
+    assert!(json["start"].is_string());
+    assert!(json["elapsed_seconds"].is_i64());
+    assert!(json["today_seconds"].is_i64());
+    Ok(())
+}
+
 #[test]
 fn test_report_json_output() -> Result<(), Box<dyn Error>> {
     let t = TestDirectory::create();
@@ -62,9 +140,746 @@ fn test_report_json_output() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+#[test]
+fn test_csv_output() -> Result<(), Box<dyn Error>> {
+    let t = TestDirectory::create();
+
+    cmd(&t).args(["start", "foo", "-n", "a, b"]).ok()?;
+    cmd(&t).arg("stop").ok()?;
+
+    let out = String::from_utf8(cmd(&t).args(["export", "--format", "csv"]).output()?.stdout)?;
+    let lines: Vec<&str> = out.lines().collect();
+    assert_eq!(lines[0], "date,project,start,end,duration,note,tags");
+    assert!(lines[1].contains(",foo,"));
+    assert!(lines[1].contains(",\"a, b\","));
+
+    cmd(&t)
+        .args(["export", "--from", "2026-10-20", "--to", "2026-10-01"])
+        .assert()
+        .code(1)
+        .stderr(predicates::str::contains(
+            "The export cannot start (2026-10-20) after it ends (2026-10-01).",
+        ));
+
+    let out = String::from_utf8(
+        cmd(&t)
+            .args([
+                "report",
+                "--format",
+                "csv",
+                "--delimiter",
+                ";",
+                "--no-header",
+            ])
+            .output()?
+            .stdout,
+    )?;
+    assert!(out.contains(";foo;0\r\n"));
+    cmd(&t)
+        .args(["export", "--delimiter", "\""])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains(
+            "Quotes and line breaks cannot be used as delimiter.",
+        ));
+    Ok(())
+}
+
+#[test]
+fn test_import() -> Result<(), Box<dyn Error>> {
+    let t = TestDirectory::create();
+    let frames = PathBuf::from("tests/fixtures/watson-frames.json");
+
+    let out = cmd(&t)
+        .args(["import", "--from", "watson", "--dry-run"])
+        .arg(&frames)
+        .output()?;
+    assert!(String::from_utf8(out.stdout)?.contains("Would import 2 entries"));
+    assert!(!t.path.join("trackie.json").exists());
+
+    cmd(&t)
+        .args(["import", "--from", "watson"])
+        .arg(&frames)
+        .assert()
+        .success()
+        .stdout("Imported 2 entries, skipped 0 duplicates\n");
+    cmd(&t)
+        .args(["import", "--from", "watson"])
+        .arg(&frames)
+        .assert()
+        .success()
+        .stdout("Imported 0 entries, skipped 2 duplicates\n");
+
+    cmd(&t)
+        .args(["import", "--from", "timewarrior", "missing.data"])
+        .assert()
+        .failure();
+    Ok(())
+}
+
+#[test]
+fn test_html_output() -> Result<(), Box<dyn Error>> {
+    let t = TestDirectory::create();
+    let file = t.path.join("report.html");
+
+    cmd(&t).args(["start", "foo"]).ok()?;
+    cmd(&t).arg("stop").ok()?;
+    cmd(&t)
+        .args(["report", "--format", "html", "-o"])
+        .arg(&file)
+        .assert()
+        .success()
+        .stdout("");
+
+    let html = std::fs::read_to_string(&file)?;
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains("foo"));
+    Ok(())
+}
+
+#[test]
+fn test_report_template() -> Result<(), Box<dyn Error>> {
+    let t = TestDirectory::create();
+    let template = t.path.join("report.j2");
+    std::fs::write(
+        &template,
+        "{% for d in days %}{% for p in d.projects %}{{ p.project }}={{ p.duration | duration('decimal') }} [{{ p.notes | join }}]\n{% endfor %}{% endfor %}",
+    )?;
+
+    cmd(&t).args(["start", "foo", "-n", "bar"]).ok()?;
+    cmd(&t).arg("stop").ok()?;
+    cmd(&t)
+        .args(["report", "--template"])
+        .arg(&template)
+        .assert()
+        .success()
+        .stdout("foo=0.00 [bar]\n");
+
+    std::fs::write(&template, "{{ unknown }}")?;
+    cmd(&t)
+        .args(["report", "--template"])
+        .arg(&template)
+        .assert()
+        .failure();
+    Ok(())
+}
+
+#[test]
+fn test_invoice() -> Result<(), Box<dyn Error>> {
+    let t = TestDirectory::create();
+    let args = [
+        "invoice",
+        "--project",
+        "foo",
+        "--period",
+        "2026-09",
+        "--format",
+        "json",
+    ];
+
+    cmd(&t).args(args).assert().failure();
+
+    std::fs::write(
+        t.path.join("config.toml"),
+        "[invoice]\ncurrency = \"EUR\"\n[rates]\nfoo = 80",
+    )?;
+    let out = String::from_utf8(cmd(&t).args(args).output()?.stdout)?;
+    let json: serde_json::Value = serde_json::from_str(&out)?;
+    assert_eq!(json["currency"], "EUR");
+    assert_eq!(json["total"], 0.0);
+
+    cmd(&t)
+        .args(["invoice", "--project", "foo", "--period", "2026-13"])
+        .assert()
+        .failure();
+    cmd(&t)
+        .args(args)
+        .args(["--round", "15m:up:report"])
+        .assert()
+        .stderr(predicates::str::contains("cannot be rounded per report"))
+        .failure();
+
+    cmd(&t).args(["project", "add", "foo"]).assert().success();
+    cmd(&t)
+        .args(["project", "alias", "foo", "f"])
+        .assert()
+        .success();
+    let mut alias_args = args;
+    alias_args[2] = "f";
+    let out = cmd(&t).args(alias_args).output()?.stdout;
+    let json: serde_json::Value = serde_json::from_slice(&out)?;
+    assert_eq!(json["project"], "foo");
+    Ok(())
+}
+
+#[test]
+fn test_balance() -> Result<(), Box<dyn Error>> {
+    let t = TestDirectory::create();
+
+    cmd(&t).arg("balance").assert().failure();
+
+    std::fs::write(t.path.join("config.toml"), "daily_target = \"8h\"")?;
+    cmd(&t)
+        .args(["calendar", "add", "2026-09-07", "holiday"])
+        .assert()
+        .success();
+    cmd(&t)
+        .args(["calendar", "list"])
+        .assert()
+        .stdout(predicates::str::contains("2026-09-07 holiday"));
+
+    // 2026-09-07 is a Monday, so only the Tuesday counts.
+    let out = cmd(&t)
+        .args([
+            "balance",
+            "--from",
+            "2026-09-07",
+            "--to",
+            "2026-09-08",
+            "--json",
+        ])
+        .output()?;
+    let json: serde_json::Value = serde_json::from_slice(&out.stdout)?;
+    assert_eq!(json["from"], "2026-09-07");
+    assert_eq!(json["to"], "2026-09-08");
+    assert_eq!(json["target"], 480);
+    assert_eq!(json["balance"], -480);
+    Ok(())
+}
+
+#[test]
+fn test_budget() -> Result<(), Box<dyn Error>> {
+    let t = TestDirectory::create();
+
+    // A typo does not create a project with a budget.
+    cmd(&t)
+        .args(["project", "budget", "foo", "40h", "--per", "month"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("Unknown project foo."));
+    cmd(&t).args(["project", "list"]).assert().stdout("");
+
+    cmd(&t).args(["project", "add", "foo"]).assert().success();
+    cmd(&t)
+        .args(["project", "budget", "foo"])
+        .assert()
+        .failure();
+    cmd(&t)
+        .args(["project", "budget", "foo", "40h", "--per", "month"])
+        .assert()
+        .success();
+    cmd(&t)
+        .args(["project", "budget", "foo"])
+        .assert()
+        .stdout(predicates::str::contains("/ 40h 00m this month"));
+
+    cmd(&t).args(["start", "foo"]).assert().success();
+    cmd(&t).arg("stop").assert().success();
+    let out = cmd(&t).args(["report", "--summary", "--json"]).output()?;
+    let json: serde_json::Value = serde_json::from_slice(&out.stdout)?;
+    assert_eq!(json["projects"][0]["project"], "foo");
+    assert_eq!(json["projects"][0]["budget"]["limit"], 2400);
+    assert_eq!(json["projects"][0]["budget"]["per"], "month");
+
+    cmd(&t)
+        .args(["report", "--summary", "--format", "csv"])
+        .assert()
+        .failure();
+    cmd(&t)
+        .args(["project", "budget", "foo", "--remove"])
+        .assert()
+        .success();
+    cmd(&t)
+        .args(["project", "budget", "foo"])
+        .assert()
+        .failure();
+    Ok(())
+}
+
+#[test]
+fn test_project_registry() -> Result<(), Box<dyn Error>> {
+    let t = TestDirectory::create();
+    std::fs::write(t.path.join("config.toml"), "unknown_projects = \"refuse\"")?;
+
+    cmd(&t)
+        .args(["project", "add", "client-x", "--client", "ACME"])
+        .assert()
+        .success();
+    cmd(&t)
+        .args(["project", "alias", "client-x", "cx"])
+        .assert()
+        .success();
+    cmd(&t)
+        .args(["start", "clinet-x"])
+        .assert()
+        .stderr(predicates::str::contains("Did you mean client-x?"))
+        .failure();
+    cmd(&t)
+        .args(["start", "cx"])
+        .assert()
+        .stdout(predicates::str::contains(
+            "Tracking time for project client-x",
+        ));
+    cmd(&t).arg("stop").assert().success();
+
+    cmd(&t)
+        .args(["project", "rename", "client-x", "client-y"])
+        .assert()
+        .stdout(predicates::str::contains("(1 entries)"));
+    cmd(&t)
+        .args(["project", "archive", "cx"])
+        .assert()
+        .success();
+    cmd(&t).args(["project", "list"]).assert().stdout("");
+    cmd(&t)
+        .args(["project", "list", "--all"])
+        .assert()
+        .stdout(predicates::str::contains("client-y"))
+        .stdout(predicates::str::contains("[ACME] (archived)"));
+
+    let out = cmd(&t).args(["report", "--json"]).output()?;
+    let json: serde_json::Value = serde_json::from_slice(&out.stdout)?;
+    assert_eq!(json["days"][0]["projects"][0]["project"], "client-y");
+    Ok(())
+}
+
+#[test]
+fn test_project_hierarchy() -> Result<(), Box<dyn Error>> {
+    let t = TestDirectory::create();
+    for project in ["acme/web", "acme/api", "other"] {
+        cmd(&t).args(["start", project]).assert().success();
+        cmd(&t).arg("stop").assert().success();
+    }
+
+    let out = cmd(&t).args(["report", "--json"]).output()?;
+    let json: serde_json::Value = serde_json::from_slice(&out.stdout)?;
+    let acme = &json["days"][0]["projects"][0];
+    assert_eq!(acme["project"], "acme");
+    assert_eq!(acme["children"][0]["project"], "acme/api");
+
+    let out = cmd(&t)
+        .args(["report", "--json", "--depth", "1"])
+        .output()?;
+    let json: serde_json::Value = serde_json::from_slice(&out.stdout)?;
+    assert!(json["days"][0]["projects"][0].get("children").is_none());
+
+    cmd(&t)
+        .args(["report", "--format", "csv", "--project", "acme/*"])
+        .assert()
+        .stdout(predicates::str::contains("acme/web"))
+        .stdout(predicates::str::contains("other").not());
+    cmd(&t).args(["report", "--depth", "0"]).assert().failure();
+    Ok(())
+}
+
+#[test]
+fn test_project_merge() -> Result<(), Box<dyn Error>> {
+    let t = TestDirectory::create();
+    for project in ["proj-old", "proj_old", "proj_old/sub", "proj"] {
+        cmd(&t).args(["start", project]).assert().success();
+        cmd(&t).arg("stop").assert().success();
+    }
+    let before = std::fs::read_to_string(t.path.join("trackie.json"))?;
+
+    cmd(&t)
+        .args([
+            "project",
+            "merge",
+            "proj-old",
+            "proj_old",
+            "proj",
+            "--dry-run",
+        ])
+        .assert()
+        .stdout("Would merge 3 entries into project proj\n");
+    cmd(&t)
+        .args(["project", "merge", "proj-old", "unknown", "proj"])
+        .assert()
+        .failure();
+    assert_eq!(
+        std::fs::read_to_string(t.path.join("trackie.json"))?,
+        before
+    );
+
+    cmd(&t)
+        .args(["project", "merge", "proj-old", "proj_old", "proj"])
+        .assert()
+        .stdout("Merged 3 entries into project proj\n");
+    cmd(&t)
+        .args(["project", "list"])
+        .assert()
+        .stdout(predicates::str::contains("proj_old").not())
+        .stdout(predicates::str::contains("proj/sub"));
+    cmd(&t)
+        .args(["project", "rename", "proj", "new", "--dry-run"])
+        .assert()
+        .stdout(predicates::str::contains("Would rename 4 entries"));
+    Ok(())
+}
+
+#[test]
+fn test_interactive_commands_need_terminal() {
+    let t = TestDirectory::create();
+    for sub_cmd in ["switch", "tui"] {
+        cmd(&t)
+            .arg(sub_cmd)
+            .write_stdin("")
+            .assert()
+            .failure()
+            .stderr(predicates::str::contains("needs an interactive terminal"));
+    }
+    cmd(&t).arg("status").assert().failure();
+}
+
+#[test]
+fn test_detect_project() -> Result<(), Box<dyn Error>> {
+    let t = TestDirectory::create();
+    let repo = t.path.join("repo");
+    std::fs::create_dir_all(repo.join(".git"))?;
+    std::fs::create_dir_all(repo.join("src"))?;
+    std::fs::write(
+        repo.join(".git").join("config"),
+        "[remote \"origin\"]\n\turl = git@github.com:beatbrot/trackie.git\n",
+    )?;
+    let website = t.path.join("website");
+    std::fs::create_dir_all(&website)?;
+    std::fs::write(
+        website.join(".trackie"),
+        "project = \"acme/website\"\ntags = [\"client\"]\n",
+    )?;
+
+    cmd(&t)
+        .arg("start")
+        .current_dir(repo.join("src"))
+        .assert()
+        .stdout(predicates::str::contains(
+            "Tracking time for project trackie",
+        ));
+    cmd(&t)
+        .args(["status", "--check-project"])
+        .current_dir(&repo)
+        .assert()
+        .stderr("");
+    cmd(&t)
+        .args(["status", "--check-project"])
+        .current_dir(&website)
+        .assert()
+        .success()
+        .stderr(predicates::str::contains(
+            "This directory belongs to project acme/website, but trackie is tracked.",
+        ));
+
+    cmd(&t)
+        .args(["start", "-t", "dev"])
+        .current_dir(&website)
+        .assert()
+        .success();
+    let out = cmd(&t).args(["status", "--json"]).output()?;
+    let json: serde_json::Value = serde_json::from_slice(&out.stdout)?;
+    assert_eq!(json["project"], "acme/website");
+    assert_eq!(json["tags"], serde_json::json!(["dev", "client"]));
+    Ok(())
+}
+
+#[test]
+fn test_report_with_commits() -> Result<(), Box<dyn Error>> {
+    let t = TestDirectory::create();
+    let repo = t.path.join("repo");
+    std::fs::create_dir_all(&repo)?;
+    let today = chrono::Local::now().date_naive();
+    let git = |args: &[&str]| {
+        std::process::Command::new("git")
+            .args(args)
+            .current_dir(&repo)
+            .env("GIT_AUTHOR_DATE", format!("{}T12:00:00", today))
+            .env("GIT_COMMITTER_DATE", format!("{}T12:00:00", today))
+            .output()
+            .unwrap()
+    };
+    git(&["init", "-q"]);
+    git(&["config", "user.email", "dev@example.com"]);
+    git(&["config", "user.name", "Dev"]);
+    git(&["commit", "-q", "--allow-empty", "-m", "Add login form"]);
+
+    let timeclock = t.path.join("work.timeclock");
+    let day = today.format("%Y/%m/%d");
+    std::fs::write(
+        &timeclock,
+        format!("i {} 11:00:00 web\no {} 13:00:00\n", day, day),
+    )?;
+    cmd(&t)
+        .args(["import", "--from", "timeclock"])
+        .arg(&timeclock)
+        .assert()
+        .success();
+
+    cmd(&t)
+        .args(["report", "-d", "1", "--with-commits"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("No repositories"));
+    cmd(&t)
+        .args(["project", "add", "web", "--repo"])
+        .arg(&repo)
+        .assert()
+        .success();
+    cmd(&t)
+        .args(["report", "-d", "1", "--with-commits"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Add login form"));
+    let out = cmd(&t)
+        .args(["report", "-d", "1", "--with-commits", "--json"])
+        .output()?;
+    let json: serde_json::Value = serde_json::from_slice(&out.stdout)?;
+    let commits = &json["days"][0]["projects"][0]["commits"];
+    assert_eq!(commits[0]["subject"], "Add login form");
+    Ok(())
+}
+
+#[test]
+fn test_git_hooks() -> Result<(), Box<dyn Error>> {
+    let t = TestDirectory::create();
+    let repo = t.path.join("shop");
+    std::fs::create_dir_all(&repo)?;
+    let git = |args: &[&str]| {
+        std::process::Command::new("git")
+            .args(args)
+            .current_dir(&repo)
+            .output()
+            .unwrap()
+    };
+    git(&["init", "-q"]);
+    git(&["config", "user.email", "dev@example.com"]);
+    git(&["config", "user.name", "Dev"]);
+
+    cmd(&t)
+        .args(["hooks", "install"])
+        .current_dir(&repo)
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("post-commit"));
+    let hook = std::fs::read_to_string(repo.join(".git/hooks/post-commit"))?;
+    assert!(hook.starts_with("#!/bin/sh\n"));
+    assert!(hook.contains("' hooks run post-commit \"$@\""));
+    cmd(&t)
+        .args(["hooks", "install"])
+        .current_dir(&repo)
+        .assert()
+        .stdout("The hooks are already installed.\n");
+
+    cmd(&t)
+        .args(["hooks", "run", "post-checkout", "a", "b", "0"])
+        .current_dir(&repo)
+        .assert()
+        .success();
+    cmd(&t).arg("status").assert().failure();
+
+    // Event hooks are not run from git hooks, so a hanging one cannot hold up git.
+    std::fs::write(
+        t.path.join("config.toml"),
+        "[hooks]\non_start = [\"sleep 30\"]",
+    )?;
+    for subject in ["Add cart", "Fix total"] {
+        git(&["commit", "-q", "--allow-empty", "-m", subject]);
+        let started = std::time::Instant::now();
+        cmd(&t)
+            .args(["hooks", "run", "post-commit"])
+            .current_dir(&repo)
+            .assert()
+            .success();
+        assert!(started.elapsed() < std::time::Duration::from_secs(5));
+    }
+    cmd(&t)
+        .args(["status", "-f", "%p: %n"])
+        .assert()
+        .success()
+        .stdout("shop: Add cart; Fix total\n");
+
+    cmd(&t)
+        .args(["hooks", "uninstall"])
+        .current_dir(&repo)
+        .assert()
+        .success();
+    assert!(!repo.join(".git/hooks/post-commit").exists());
+
+    let python = "#!/usr/bin/env python3\nprint('checked out')\n";
+    std::fs::write(repo.join(".git/hooks/post-checkout"), python)?;
+    cmd(&t)
+        .args(["hooks", "install"])
+        .current_dir(&repo)
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains(
+            "post-checkout as it is no sh or bash script",
+        ));
+    assert_eq!(
+        std::fs::read_to_string(repo.join(".git/hooks/post-checkout"))?,
+        python
+    );
+    assert!(!repo.join(".git/hooks/post-commit").exists());
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn test_event_hooks() -> Result<(), Box<dyn Error>> {
+    let t = TestDirectory::create();
+    let events_file = t.path.join("events");
+    std::fs::write(
+        t.path.join("config.toml"),
+        format!(
+            "[hooks]\n\
+             on_start = [\"echo $TRACKIE_EVENT $TRACKIE_PROJECT >> '{0}'\"]\n\
+             on_switch = [\"echo $TRACKIE_PREVIOUS_PROJECT $TRACKIE_EVENT $TRACKIE_PROJECT >> '{0}'\"]\n\
+             on_stop = [\"cat >> '{0}'\", \"echo unreachable >&2; exit 1\"]\n",
+            events_file.display()
+        ),
+    )?;
+
+    cmd(&t).args(["start", "web"]).assert().success().stderr("");
+    cmd(&t).args(["start", "api"]).assert().success();
+    cmd(&t)
+        .arg("stop")
+        .assert()
+        .success()
+        .stderr(predicates::str::contains(
+            "WARN: Hook `echo unreachable >&2; exit 1` failed: unreachable",
+        ));
+    cmd(&t).arg("status").assert().failure();
+
+    let events = std::fs::read_to_string(&events_file)?;
+    let lines: Vec<&str> = events.lines().collect();
+    assert_eq!(lines[..2], ["start web", "web switch api"]);
+    let stop: serde_json::Value = serde_json::from_str(lines[2])?;
+    assert_eq!(stop["event"], "stop");
+    assert_eq!(stop["stopped"]["project_name"], "api");
+    assert!(stop["started"].is_null());
+
+    // Discarded work was never tracked, so no hook learns about it.
+    let start = chrono::Local::now() - chrono::Duration::hours(12);
+    std::fs::write(
+        t.path.join("trackie.json"),
+        serde_json::json!({
+            "pending": { "project_name": "web", "start": start },
+            "entries": {},
+        })
+        .to_string(),
+    )?;
+    cmd(&t)
+        .args(["stop", "--idle-policy", "discard"])
+        .assert()
+        .success()
+        .stderr(predicates::str::contains("Discarded"));
+    assert_eq!(std::fs::read_to_string(&events_file)?, events);
+    Ok(())
+}
+
+#[test]
+fn test_idle_policies() -> Result<(), Box<dyn Error>> {
+    let t = TestDirectory::create();
+    let start = chrono::Local::now() - chrono::Duration::hours(12);
+    let track_since_morning = || {
+        std::fs::write(
+            t.path.join("trackie.json"),
+            serde_json::json!({
+                "pending": { "project_name": "web", "start": start },
+                "entries": {},
+            })
+            .to_string(),
+        )
+    };
+
+    track_since_morning()?;
+    // Prompts that embed the status are not cluttered with the warning.
+    cmd(&t)
+        .args(["status", "-f", "%p"])
+        .assert()
+        .success()
+        .stdout("web\n")
+        .stderr("");
+    let end = (start + chrono::Duration::hours(2))
+        .format("%H:%M")
+        .to_string();
+    cmd(&t)
+        .args(["stop", "--idle-policy", "trim", "--idle-end", &end])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Tracked 01h"))
+        .stderr(format!("Ended project web at {}\n", end));
+
+    track_since_morning()?;
+    cmd(&t)
+        .args(["resume", "--idle-policy", "split"])
+        .assert()
+        .success()
+        .stdout("Tracking time for project web\n");
+    cmd(&t)
+        .args(["status", "-f", "%D"])
+        .assert()
+        .success()
+        .stdout("00h 00m\n")
+        .stderr("");
+    let out = cmd(&t).args(["export", "--no-header"]).output()?;
+    assert_eq!(String::from_utf8(out.stdout)?.lines().count(), 1);
+
+    cmd(&t).arg("stop").assert().success();
+    track_since_morning()?;
+    cmd(&t)
+        .args(["stop", "--idle-policy", "discard"])
+        .assert()
+        .success()
+        .stdout("");
+    cmd(&t).arg("status").assert().failure();
+    let out = cmd(&t).args(["export", "--no-header"]).output()?;
+    assert_eq!(String::from_utf8(out.stdout)?.lines().count(), 0);
+
+    track_since_morning()?;
+    cmd(&t)
+        .arg("stop")
+        .assert()
+        .success()
+        .stderr(predicates::str::contains(
+            "WARN: Project web has been tracked for 12h",
+        ));
+    Ok(())
+}
+
+#[test]
+fn test_project_completion() {
+    let t = TestDirectory::create();
+    for project in ["older", "newer"] {
+        cmd(&t).args(["start", project]).assert().success();
+        cmd(&t).arg("stop").assert().success();
+    }
+    cmd(&t)
+        .args(["project", "add", "registered"])
+        .assert()
+        .success();
+
+    for args in [
+        vec!["start", ""],
+        vec!["report", "--project", ""],
+        vec!["project", "info", ""],
+    ] {
+        cmd(&t)
+            .env("COMPLETE", "fish")
+            .args(["--", "trackie"])
+            .args(args)
+            .assert()
+            .success()
+            .stdout(predicates::str::starts_with("newer\nolder\nregistered\n"));
+    }
+    cmd(&t)
+        .args(["completion", "zsh"])
+        .assert()
+        .stdout(predicates::str::starts_with("#compdef trackie"));
+}
+
 fn cmd(td: &TestDirectory) -> Command {
     let mut r = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
     r.env("TRACKIE_CONFIG", td.path.join("trackie.json"));
+    r.env("TRACKIE_SETTINGS", td.path.join("config.toml"));
     r
 }
 
@@ -84,6 +899,8 @@ impl TestDirectory {
             .join(n.to_string());
 
         std::fs::create_dir_all(path.clone()).unwrap();
+        // Absolute, so that commands can run in subdirectories.
+        let path = path.canonicalize().unwrap();
         Self { path }
     }
 }